@@ -0,0 +1,67 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use simd_itertools::FilterSimd;
+
+const LEN: usize = 1 << 20;
+
+/// Every `period`th element is 0 and the rest are 1, `None` for no 0 at all.
+fn data<T: From<u8> + Copy>(period: Option<usize>) -> Vec<T> {
+    (0..LEN)
+        .map(|i| match period {
+            Some(period) if i % period == 0 => T::from(0),
+            _ => T::from(1),
+        })
+        .collect()
+}
+
+fn bench_type<T>(c: &mut Criterion, name: &str)
+where
+    T: From<u8> + Copy + PartialEq,
+{
+    let mut group = c.benchmark_group(format!("filter_{}", name));
+    let zero = T::from(0);
+    let densities = [
+        ("none", None),
+        ("sparse", Some(1000)),
+        ("half", Some(2)),
+        ("all", Some(1)),
+    ];
+    for (density, period) in densities {
+        let v = data::<T>(period);
+        group.bench_with_input(BenchmarkId::new("filter_simd", density), &v, |b, v| {
+            b.iter(|| {
+                black_box(v)
+                    .iter()
+                    .filter_simd(|x| *x == zero)
+                    .collect::<Vec<T>>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("filter_into_simd", density), &v, |b, v| {
+            b.iter(|| {
+                let mut out = Vec::new();
+                black_box(v)
+                    .iter()
+                    .filter_into_simd(&mut out, |x| *x == zero);
+                out
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("filter", density), &v, |b, v| {
+            b.iter(|| {
+                black_box(v)
+                    .iter()
+                    .filter(|x| **x == zero)
+                    .copied()
+                    .collect::<Vec<T>>()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_filter(c: &mut Criterion) {
+    bench_type::<u8>(c, "u8");
+    bench_type::<i32>(c, "i32");
+    bench_type::<i64>(c, "i64");
+}
+
+criterion_group!(benches, bench_filter);
+criterion_main!(benches);
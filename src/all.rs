@@ -1,24 +1,25 @@
+use crate::dispatch::simd_kernel;
 use crate::LANE_COUNT;
-use multiversion::multiversion;
-use std::slice;
+use core::slice;
 
-#[multiversion(targets = "simd")]
-fn all_simd_internal<F, T>(v: &[T], f: F) -> bool
+pub(crate) fn all_simd_internal<F, T>(v: &[T], f: F) -> bool
 where
     F: Fn(&T) -> bool,
 {
-    let mut chunks = v.chunks_exact(LANE_COUNT);
-    for chunk in chunks.by_ref() {
-        if !chunk.iter().fold(true, |acc, x| acc & f(x)) {
-            return false;
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        for chunk in chunks.by_ref() {
+            if !chunk.iter().fold(true, |acc, x| acc & f(x)) {
+                return false;
+            }
         }
+        chunks.remainder().iter().fold(true, |acc, x| acc & f(x))
     }
-    chunks.remainder().iter().fold(true, |acc, x| acc & f(x))
 }
 
 pub trait AllSimd<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn all_simd<F>(&self, f: F) -> bool
     where
@@ -27,7 +28,7 @@ where
 
 impl<'a, T> AllSimd<'a, T> for slice::Iter<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn all_simd<F>(&self, f: F) -> bool
     where
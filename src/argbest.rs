@@ -0,0 +1,139 @@
+use crate::dispatch::simd_kernel;
+use crate::LANE_COUNT;
+
+/// Index of the first element that no other element `is_better` than.
+///
+/// Every lane keeps its own best value and the chunk it came from, so the
+/// slice is only read once. `is_better` must be a strict weak ordering.
+pub(crate) fn argbest_autovec<T, F>(arr: &[T], is_better: F) -> Option<usize>
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+{
+    simd_kernel! {
+        argbest_kernel(arr, &is_better)
+    }
+}
+
+/// Body of `argbest_autovec`, for callers that already dispatched.
+#[inline(always)]
+pub(crate) fn argbest_kernel<T, F>(arr: &[T], is_better: &F) -> Option<usize>
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+{
+    // Nothing is ever better under the second ordering, so once inlined
+    // its lanes never change and compile away.
+    argbest2_kernel(arr, is_better, &|_: &T, _: &T| false).map(|(best, _)| best)
+}
+
+/// Same as `argbest_autovec` but tracks two orderings in the same sweep.
+pub(crate) fn argbest2_autovec<T, F, G>(
+    arr: &[T],
+    is_better_a: F,
+    is_better_b: G,
+) -> Option<(usize, usize)>
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    simd_kernel! {
+        argbest2_kernel(arr, &is_better_a, &is_better_b)
+    }
+}
+
+#[inline(always)]
+fn argbest2_kernel<T, F, G>(arr: &[T], is_better_a: &F, is_better_b: &G) -> Option<(usize, usize)>
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    let mut chunks = arr.chunks_exact(LANE_COUNT);
+    let mut best = match chunks.next() {
+        Some(first) => {
+            let mut lanes_a = Lanes::new(first);
+            let mut lanes_b = Lanes::new(first);
+            for (chunk_idx, chunk) in chunks.by_ref().enumerate() {
+                lanes_a.update(chunk_idx + 1, chunk, is_better_a);
+                lanes_b.update(chunk_idx + 1, chunk, is_better_b);
+            }
+            Some((lanes_a.best(is_better_a), lanes_b.best(is_better_b)))
+        }
+        None => None,
+    };
+    let offset = arr.len() - chunks.remainder().len();
+    for (i, x) in chunks.remainder().iter().enumerate() {
+        let i = offset + i;
+        best = Some(match best {
+            Some((a, b)) => (
+                if is_better_a(x, &arr[a]) { i } else { a },
+                if is_better_b(x, &arr[b]) { i } else { b },
+            ),
+            None => (i, i),
+        });
+    }
+    best
+}
+
+/// The best value of every lane and the chunk it came from.
+struct Lanes<T> {
+    vals: [T; LANE_COUNT],
+    chunk_idxs: [usize; LANE_COUNT],
+}
+
+impl<T: Copy> Lanes<T> {
+    #[inline(always)]
+    fn new(first: &[T]) -> Self {
+        Lanes {
+            vals: first.try_into().unwrap(),
+            chunk_idxs: [0; LANE_COUNT],
+        }
+    }
+
+    #[inline(always)]
+    fn update<F>(&mut self, chunk_idx: usize, chunk: &[T], is_better: &F)
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let lanes = self.vals.iter_mut().zip(self.chunk_idxs.iter_mut());
+        for ((val, idx), x) in lanes.zip(chunk) {
+            let better = is_better(x, val);
+            *val = if better { *x } else { *val };
+            *idx = if better { chunk_idx } else { *idx };
+        }
+    }
+
+    #[inline(always)]
+    fn best<F>(&self, is_better: &F) -> usize
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        reduce_lanes(&self.vals, &self.chunk_idxs, is_better)
+    }
+}
+
+/// Picks the best lane. Equally good lanes are resolved by their index so
+/// that the first occurrence wins.
+#[inline(always)]
+fn reduce_lanes<T, F>(
+    vals: &[T; LANE_COUNT],
+    chunk_idxs: &[usize; LANE_COUNT],
+    is_better: &F,
+) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut best_lane = 0;
+    for lane in 1..LANE_COUNT {
+        let idx = chunk_idxs[lane] * LANE_COUNT + lane;
+        let best_idx = chunk_idxs[best_lane] * LANE_COUNT + best_lane;
+        if is_better(&vals[lane], &vals[best_lane])
+            || (!is_better(&vals[best_lane], &vals[lane]) && idx < best_idx)
+        {
+            best_lane = lane;
+        }
+    }
+    chunk_idxs[best_lane] * LANE_COUNT + best_lane
+}
@@ -1,9 +1,10 @@
-use crate::position::PositionSimd;
-use std::slice;
+use crate::argbest::argbest_autovec;
+use crate::float::{with_nan_cmp, NanPolicy, SimdFloat};
+use core::slice;
 
 pub trait ArgminSimd<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn argmin_simd(&self) -> Option<usize>;
     fn argmin_simd_fast(&self) -> Option<usize>;
@@ -11,22 +12,39 @@ where
 
 impl<'a, T> ArgminSimd<'a, T> for slice::Iter<'a, T>
 where
-    T: std::cmp::PartialEq + std::cmp::PartialOrd + Copy + std::cmp::Ord,
+    T: core::cmp::PartialEq + core::cmp::PartialOrd + Copy + core::cmp::Ord,
 {
     fn argmin_simd(&self) -> Option<usize> {
-        match self.as_slice().iter().copied().min() {
-            Some(min) => self.position_simd(|x| *x == min),
-            None => None,
-        }
+        argbest_autovec(self.as_slice(), |x, best| x < best)
     }
+    /// Same as `argmin_simd`, kept for compatibility.
     fn argmin_simd_fast(&self) -> Option<usize> {
-        match self
-            .as_slice()
-            .iter()
-            .reduce(|a, b| if a < b { a } else { b })
-        {
-            Some(min) => self.position_simd(|x| *x == *min),
-            None => None,
+        self.argmin_simd()
+    }
+}
+
+pub trait ArgminFloatSimd<'a, T>
+where
+    T: SimdFloat,
+{
+    fn argmin_float_simd(&self, policy: NanPolicy) -> Option<usize>;
+}
+
+impl<'a, T> ArgminFloatSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: SimdFloat,
+{
+    /// Index of the first minimum under the given NaN policy.
+    fn argmin_float_simd(&self, policy: NanPolicy) -> Option<usize> {
+        let arr = self.as_slice();
+        let idx = with_nan_cmp!(policy, T, |is_less, _is_greater| {
+            argbest_autovec(arr, is_less)
+        });
+        match (policy, idx) {
+            // NaN is worse than every number, so it is only picked when
+            // everything is NaN.
+            (NanPolicy::Ignore, Some(idx)) if arr[idx].is_nan() => None,
+            _ => idx,
         }
     }
 }
@@ -34,6 +52,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use core::cmp::Ordering;
     use rand::distributions::Standard;
     use rand::prelude::Distribution;
     use rand::Rng;
@@ -91,6 +110,76 @@ mod tests {
         }
     }
 
+    fn argmin_float_reference<T: SimdFloat>(v: &[T], policy: NanPolicy) -> Option<usize> {
+        if policy == NanPolicy::Propagate {
+            if let Some(idx) = v.iter().position(|x| x.is_nan()) {
+                return Some(idx);
+            }
+        }
+        let mut best: Option<usize> = None;
+        for (i, x) in v.iter().enumerate() {
+            let better = match (policy, best) {
+                (NanPolicy::TotalOrder, None) => true,
+                (_, None) => !x.is_nan(),
+                (NanPolicy::TotalOrder, Some(b)) => x.total_cmp(&v[b]) == Ordering::Less,
+                (_, Some(b)) => *x < v[b],
+            };
+            if better {
+                best = Some(i);
+            }
+        }
+        best
+    }
+
+    fn test_float_for_type<T>()
+    where
+        T: SimdFloat + From<f32> + Debug,
+    {
+        let specials = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+            -f32::NAN,
+        ];
+        let mut rng = rand::thread_rng();
+        for len in 0..1000 {
+            for nan_chance in [0.0, 0.01, 0.5, 1.0] {
+                let v: Vec<T> = (0..len)
+                    .map(|_| {
+                        if rng.gen_bool(nan_chance) {
+                            T::from(if rng.gen() { f32::NAN } else { -f32::NAN })
+                        } else if rng.gen_bool(0.1) {
+                            T::from(specials[rng.gen_range(0..specials.len())])
+                        } else {
+                            T::from(rng.gen_range(-100..100) as f32)
+                        }
+                    })
+                    .collect();
+                for policy in [
+                    NanPolicy::Ignore,
+                    NanPolicy::Propagate,
+                    NanPolicy::TotalOrder,
+                ] {
+                    let ans = v.iter().argmin_float_simd(policy);
+                    let correct = argmin_float_reference(&v, policy);
+                    assert_eq!(
+                        ans,
+                        correct,
+                        "Failed for length {}, policy {:?} and type {:?} {:?}",
+                        len,
+                        policy,
+                        std::any::type_name::<T>(),
+                        v
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_simd_min() {
         test_simd_for_type::<i8>();
@@ -104,4 +193,10 @@ mod tests {
         test_simd_for_type::<usize>();
         test_simd_for_type::<isize>();
     }
+
+    #[test]
+    fn test_simd_min_float() {
+        test_float_for_type::<f32>();
+        test_float_for_type::<f64>();
+    }
 }
@@ -0,0 +1,150 @@
+use crate::argbest::argbest2_autovec;
+use crate::float::{with_nan_cmp, NanPolicy, SimdFloat};
+use core::slice;
+
+pub trait ArgMinMaxSimd<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    fn argminmax_simd(&self) -> Option<(usize, usize)>;
+}
+
+impl<'a, T> ArgMinMaxSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq + core::cmp::PartialOrd + Copy + core::cmp::Ord,
+{
+    /// Indices of the first minimum and the first maximum, in one pass.
+    fn argminmax_simd(&self) -> Option<(usize, usize)> {
+        argbest2_autovec(self.as_slice(), |x, min| x < min, |x, max| x > max)
+    }
+}
+
+pub trait ArgMinMaxFloatSimd<'a, T>
+where
+    T: SimdFloat,
+{
+    fn argminmax_float_simd(&self, policy: NanPolicy) -> Option<(usize, usize)>;
+}
+
+impl<'a, T> ArgMinMaxFloatSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: SimdFloat,
+{
+    /// Indices of the first minimum and the first maximum under the given
+    /// NaN policy, in one pass.
+    fn argminmax_float_simd(&self, policy: NanPolicy) -> Option<(usize, usize)> {
+        let arr = self.as_slice();
+        let idx = with_nan_cmp!(policy, T, |is_less, is_greater| {
+            argbest2_autovec(arr, is_less, is_greater)
+        });
+        match (policy, idx) {
+            (NanPolicy::Ignore, Some((min, _))) if arr[min].is_nan() => None,
+            _ => idx,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArgmaxFloatSimd, ArgminFloatSimd};
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Debug
+            + Copy
+            + Default
+            + std::cmp::PartialEq
+            + Ord,
+        Standard: Distribution<T>,
+    {
+        for len in 0..1000 {
+            for _ in 0..5 {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+                let ans = v.iter().argminmax_simd();
+                let correct = match (v.iter().min(), v.iter().max()) {
+                    (Some(min), Some(max)) => Some((
+                        v.iter().position(|x| x == min).unwrap(),
+                        v.iter().position(|x| x == max).unwrap(),
+                    )),
+                    _ => None,
+                };
+                assert_eq!(
+                    ans,
+                    correct,
+                    "Failed for length {} and type {:?} {:?}",
+                    len,
+                    std::any::type_name::<T>(),
+                    v
+                );
+            }
+        }
+    }
+
+    fn test_float_for_type<T>()
+    where
+        T: SimdFloat + From<f32> + Debug,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..1000 {
+            for nan_chance in [0.0, 0.01, 0.5, 1.0] {
+                let v: Vec<T> = (0..len)
+                    .map(|_| match rng.gen_bool(nan_chance) {
+                        true => T::from(if rng.gen() { f32::NAN } else { -f32::NAN }),
+                        false => T::from(rng.gen_range(-100..100) as f32),
+                    })
+                    .collect();
+                for policy in [
+                    NanPolicy::Ignore,
+                    NanPolicy::Propagate,
+                    NanPolicy::TotalOrder,
+                ] {
+                    let ans = v.iter().argminmax_float_simd(policy);
+                    let correct = v
+                        .iter()
+                        .argmin_float_simd(policy)
+                        .zip(v.iter().argmax_float_simd(policy));
+                    assert_eq!(
+                        ans,
+                        correct,
+                        "Failed for length {}, policy {:?} and type {:?} {:?}",
+                        len,
+                        policy,
+                        std::any::type_name::<T>(),
+                        v
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_minmax() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+
+    #[test]
+    fn test_simd_minmax_float() {
+        test_float_for_type::<f32>();
+        test_float_for_type::<f64>();
+    }
+}
@@ -1,27 +1,47 @@
+use crate::dispatch::simd_kernel;
 use crate::LANE_COUNT;
-use std::slice;
+use core::slice;
 
+pub(crate) fn contains_simd_internal<T>(v: &[T], elem: &T) -> bool
+where
+    T: PartialEq,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        for chunk in chunks.by_ref() {
+            if chunk.iter().fold(false, |acc, x| acc | (x == elem)) {
+                return true;
+            }
+        }
+        chunks.remainder().contains(elem)
+    }
+}
+
+/// Works for any `PartialEq` type and leaves the vectorization to LLVM.
+/// For the primitive types `EqSimd::contains_eq_simd` gives the same
+/// answer with hand-written SSE2, AVX2 and AVX-512BW kernels on x86_64,
+/// which do not depend on how the compiler treats this loop:
+/// ```
+/// use simd_itertools::{ContainsSimd, EqSimd};
+///
+/// let v = [1u8, 2, 3];
+/// assert_eq!(v.iter().contains_eq_simd(&2), v.iter().contains_simd(&2));
+/// ```
 pub trait ContainsSimd<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn contains_simd(&self, elem: &T) -> bool;
 }
 impl<'a, T> ContainsSimd<'a, T> for slice::Iter<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn contains_simd(&self, elem: &T) -> bool
     where
         T: PartialEq,
     {
-        let mut chunks = self.as_slice().chunks_exact(LANE_COUNT);
-        for chunk in chunks.by_ref() {
-            if chunk.iter().fold(false, |acc, x| acc | (x == elem)) {
-                return true;
-            }
-        }
-        chunks.remainder().contains(elem)
+        contains_simd_internal(self.as_slice(), elem)
     }
 }
 
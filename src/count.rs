@@ -0,0 +1,132 @@
+use crate::dispatch::simd_kernel;
+use crate::LANE_COUNT;
+use core::slice;
+
+pub(crate) fn count_simd_internal<F, T>(v: &[T], f: F) -> usize
+where
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        count_kernel(v, &f)
+    }
+}
+
+/// Body of `count_simd_internal`, for callers that already dispatched.
+#[inline(always)]
+pub(crate) fn count_kernel<F, T>(v: &[T], f: &F) -> usize
+where
+    F: Fn(&T) -> bool,
+{
+    // Lane counters are kept as u8 so that more of them fit in a register.
+    // They are flushed into `total` before any of them can overflow.
+    const FLUSH_EVERY: usize = u8::MAX as usize;
+    let mut total = 0;
+    let mut counts = [0u8; LANE_COUNT];
+    let mut chunks = v.chunks_exact(LANE_COUNT);
+    for (chunk_idx, chunk) in chunks.by_ref().enumerate() {
+        for (count, x) in counts.iter_mut().zip(chunk) {
+            *count += f(x) as u8;
+        }
+        if chunk_idx % FLUSH_EVERY == FLUSH_EVERY - 1 {
+            total += counts.iter().map(|c| *c as usize).sum::<usize>();
+            counts = [0; LANE_COUNT];
+        }
+    }
+    total += counts.iter().map(|c| *c as usize).sum::<usize>();
+    total + chunks.remainder().iter().filter(|x| f(x)).count()
+}
+
+pub trait CountSimd<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    fn count_simd<F>(&self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool;
+}
+
+impl<'a, T> CountSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    /// Counts the elements matching `f`.
+    ///
+    /// Unlike `filter_simd(f).count()` there is no early exit, so the speed
+    /// does not depend on how many elements match.
+    fn count_simd<F>(&self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        count_simd_internal(self.as_slice(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + std::cmp::PartialEq
+            + std::cmp::PartialOrd,
+        Standard: Distribution<T>,
+    {
+        for len in 0..5000 {
+            let ops = [
+                |x: &T| *x == T::default(),
+                |x: &T| *x != T::default(),
+                |x: &T| *x < T::default(),
+                |x: &T| *x > T::default(),
+                |x: &T| [T::default()].contains(x),
+            ];
+
+            for op in ops {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+
+                let ans = v.iter().count_simd(op);
+                let correct = v.iter().filter(|x| op(x)).count();
+                assert_eq!(
+                    ans,
+                    correct,
+                    "Failed for length {} and type {:?}",
+                    len,
+                    std::any::type_name::<T>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_count() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+
+    #[test]
+    fn test_simd_count_no_lane_overflow() {
+        // Every lane hits on every chunk, well past the u8 flush interval.
+        let v = vec![1u8; LANE_COUNT * 1000 + 7];
+        assert_eq!(v.iter().count_simd(|x| *x == 1), v.len());
+    }
+}
@@ -0,0 +1,420 @@
+use core::fmt;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Environment variable read on first use to override the target of every
+/// kernel, e.g. `SIMD_ITERTOOLS_TARGET=sse2`.
+pub const TARGET_ENV_VAR: &str = "SIMD_ITERTOOLS_TARGET";
+
+/// Instruction sets the kernels are specialized for.
+///
+/// Ordered from the least to the most capable within an architecture.
+/// `Generic` is the plain baseline of any architecture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Target {
+    Generic,
+    Sse2,
+    Sse42,
+    Avx2,
+    Avx512bw,
+    Neon,
+}
+
+const TARGETS: [Target; 6] = [
+    Target::Generic,
+    Target::Sse2,
+    Target::Sse42,
+    Target::Avx2,
+    Target::Avx512bw,
+    Target::Neon,
+];
+
+#[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64")))]
+macro_rules! x86_detected {
+    ($($feature:tt),*) => {
+        true $(&& std::arch::is_x86_feature_detected!($feature))*
+    };
+}
+#[cfg(all(not(feature = "std"), any(target_arch = "x86", target_arch = "x86_64")))]
+macro_rules! x86_detected {
+    ($($feature:tt),*) => {
+        true $(&& cfg!(target_feature = $feature))*
+    };
+}
+
+impl Target {
+    /// Whether this CPU can run code for `self`. Without `std` this only
+    /// knows the target features enabled at compile time. `Avx512bw` also
+    /// needs the `avx512` feature.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Target::Generic => true,
+            #[cfg(target_arch = "x86_64")]
+            Target::Sse2 => true,
+            #[cfg(target_arch = "x86")]
+            Target::Sse2 => x86_detected!("sse2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Target::Sse42 => x86_detected!("sse4.2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Target::Avx2 => x86_detected!("avx2", "fma"),
+            #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+            Target::Avx512bw => x86_detected!("avx512f", "avx512bw", "avx2", "fma"),
+            #[cfg(all(feature = "std", target_arch = "aarch64"))]
+            Target::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[cfg(all(not(feature = "std"), target_arch = "aarch64"))]
+            Target::Neon => cfg!(target_feature = "neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Target::Generic => "generic",
+            Target::Sse2 => "sse2",
+            Target::Sse42 => "sse4.2",
+            Target::Avx2 => "avx2",
+            Target::Avx512bw => "avx512bw",
+            Target::Neon => "neon",
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Error for a string that names no `Target`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTargetError;
+
+impl fmt::Display for ParseTargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown target, expected one of generic, sse2, sse4.2, avx2, avx512bw, neon")
+    }
+}
+
+impl FromStr for Target {
+    type Err = ParseTargetError;
+
+    /// Parses the names printed by `Display`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        TARGETS
+            .into_iter()
+            .find(|target| target.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or(ParseTargetError)
+    }
+}
+
+/// Why a target cannot be selected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TargetError {
+    /// The name is not one of `Target`.
+    Unknown(ParseTargetError),
+    /// This CPU cannot run the target.
+    Unsupported(Target),
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetError::Unknown(err) => err.fmt(f),
+            TargetError::Unsupported(target) => {
+                write!(f, "{} is not supported by this CPU", target)
+            }
+        }
+    }
+}
+
+impl From<ParseTargetError> for TargetError {
+    fn from(err: ParseTargetError) -> Self {
+        TargetError::Unknown(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseTargetError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TargetError {}
+
+/// Most capable target this CPU supports.
+pub fn detected_target() -> Target {
+    TARGETS
+        .into_iter()
+        .rev()
+        .find(|target| target.is_supported())
+        .unwrap_or(Target::Generic)
+}
+
+const UNSET: u8 = u8::MAX;
+static SELECTED: AtomicU8 = AtomicU8::new(UNSET);
+
+fn from_byte(byte: u8) -> Target {
+    match byte {
+        0 => Target::Generic,
+        1 => Target::Sse2,
+        2 => Target::Sse42,
+        3 => Target::Avx2,
+        4 => Target::Avx512bw,
+        5 => Target::Neon,
+        _ => unreachable!("invalid target byte {}", byte),
+    }
+}
+
+#[cfg(feature = "std")]
+fn supported_target(name: &str) -> Result<Target, TargetError> {
+    let target = name.parse::<Target>()?;
+    if target.is_supported() {
+        Ok(target)
+    } else {
+        Err(TargetError::Unsupported(target))
+    }
+}
+
+/// Target named by `SIMD_ITERTOOLS_TARGET`, `Ok(None)` if it is not set.
+/// Without `std` there is no environment and this is always `Ok(None)`.
+#[cfg(feature = "std")]
+pub fn target_from_env() -> Result<Option<Target>, TargetError> {
+    match std::env::var(TARGET_ENV_VAR) {
+        Ok(value) => supported_target(&value).map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(not(feature = "std"))]
+pub fn target_from_env() -> Result<Option<Target>, TargetError> {
+    Ok(None)
+}
+
+/// Target every kernel runs with: the one passed to `force_target`, else
+/// the one from `SIMD_ITERTOOLS_TARGET`, else `detected_target()`.
+///
+/// An unknown or unsupported `SIMD_ITERTOOLS_TARGET` falls back to
+/// `detected_target()`, call `target_from_env` to check it.
+pub fn selected_target() -> Target {
+    match SELECTED.load(Ordering::Relaxed) {
+        UNSET => {
+            let target = target_from_env()
+                .ok()
+                .flatten()
+                .unwrap_or_else(detected_target);
+            // Someone may have forced a target in the meantime.
+            match SELECTED.compare_exchange(
+                UNSET,
+                target as u8,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => target,
+                Err(forced) => from_byte(forced),
+            }
+        }
+        selected => from_byte(selected),
+    }
+}
+
+/// Makes every later call use the kernels for `target`, for benchmarking or
+/// testing each path on one machine.
+///
+/// The autovectorized kernels are compiled for `Sse42`, `Avx2` and
+/// `Avx512bw`, and for `Sse2` on 32-bit x86. The other targets run the
+/// baseline build of the architecture.
+/// An `EqSimd` search without its own kernel for `target` uses the next less
+/// capable one, `Generic` always uses the autovectorized kernels.
+///
+/// Panics if this CPU does not support `target`, see `try_force_target`.
+pub fn force_target(target: Target) {
+    if let Err(err) = try_force_target(target) {
+        panic!("{}", err);
+    }
+}
+
+/// Like `force_target`, but returns an error instead of panicking.
+pub fn try_force_target(target: Target) -> Result<(), TargetError> {
+    if !target.is_supported() {
+        return Err(TargetError::Unsupported(target));
+    }
+    SELECTED.store(target as u8, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Undoes `force_target`. The next call picks the target again, reading
+/// `SIMD_ITERTOOLS_TARGET` anew.
+pub fn reset_target() {
+    SELECTED.store(UNSET, Ordering::Relaxed);
+}
+
+// The clones of the autovectorized kernels. `kernel` is a closure called
+// from a single place, so LLVM inlines it and compiles the kernel with the
+// enabled features.
+
+#[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+#[target_feature(enable = "avx512f,avx512bw,avx2,fma")]
+pub(crate) unsafe fn with_avx512bw<R>(kernel: impl FnOnce() -> R) -> R {
+    kernel()
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2,fma")]
+pub(crate) unsafe fn with_avx2<R>(kernel: impl FnOnce() -> R) -> R {
+    kernel()
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse4.2")]
+pub(crate) unsafe fn with_sse42<R>(kernel: impl FnOnce() -> R) -> R {
+    kernel()
+}
+
+/// SSE2 is only optional on 32-bit x86.
+#[cfg(target_arch = "x86")]
+#[target_feature(enable = "sse2")]
+pub(crate) unsafe fn with_sse2<R>(kernel: impl FnOnce() -> R) -> R {
+    kernel()
+}
+
+/// Runs the kernel in the body once, compiled for `selected_target()`.
+///
+/// Expands the body into every arm, so it is its own closure in each and
+/// `return` leaves the kernel. Use it as the whole body of the function.
+macro_rules! simd_kernel {
+    ($($body:tt)*) => {
+        match crate::dispatch::selected_target() {
+            // SAFETY: `selected_target` only returns targets this CPU supports.
+            #[cfg(all(feature = "avx512", any(target_arch = "x86", target_arch = "x86_64")))]
+            crate::dispatch::Target::Avx512bw => {
+                let kernel = move || { $($body)* };
+                unsafe { crate::dispatch::with_avx512bw(kernel) }
+            }
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            crate::dispatch::Target::Avx2 => {
+                let kernel = move || { $($body)* };
+                unsafe { crate::dispatch::with_avx2(kernel) }
+            }
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            crate::dispatch::Target::Sse42 => {
+                let kernel = move || { $($body)* };
+                unsafe { crate::dispatch::with_sse42(kernel) }
+            }
+            #[cfg(target_arch = "x86")]
+            crate::dispatch::Target::Sse2 => {
+                let kernel = move || { $($body)* };
+                unsafe { crate::dispatch::with_sse2(kernel) }
+            }
+            _ => { $($body)* }
+        }
+    };
+}
+pub(crate) use simd_kernel;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{EqSimd, PositionSimd, SumSimd};
+    use std::sync::{Mutex, MutexGuard};
+
+    /// Held by every test that forces a target, as `SELECTED` is shared by
+    /// all the tests running in parallel.
+    static TARGET_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock_target() -> MutexGuard<'static, ()> {
+        // A failed test must not fail every later one.
+        TARGET_LOCK.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Calls `reset_target` when dropped, so a panicking test does not leave
+    /// its target forced for the next one.
+    struct ResetTarget;
+
+    impl Drop for ResetTarget {
+        fn drop(&mut self) {
+            reset_target();
+        }
+    }
+
+    /// Runs `f` once with every target this CPU supports forced.
+    pub(crate) fn for_each_target(mut f: impl FnMut(Target)) {
+        let _lock = lock_target();
+        // Declared after the lock, so the target is reset before it is
+        // released.
+        let _reset = ResetTarget;
+        for target in TARGETS.into_iter().filter(|target| target.is_supported()) {
+            force_target(target);
+            f(target);
+        }
+    }
+
+    #[test]
+    fn test_target_names() {
+        for target in TARGETS {
+            assert_eq!(target.to_string().parse::<Target>(), Ok(target));
+        }
+        assert_eq!(" AVX2".parse::<Target>(), Ok(Target::Avx2));
+        assert_eq!("avx".parse::<Target>(), Err(ParseTargetError));
+    }
+
+    #[test]
+    fn test_target_detection() {
+        assert!(detected_target().is_supported());
+        assert!(Target::Generic.is_supported());
+        assert!(!(Target::Sse2.is_supported() && Target::Neon.is_supported()));
+    }
+
+    #[test]
+    fn test_force_target() {
+        let v: Vec<u16> = (0..1000).map(|i| i % 7).collect();
+        for_each_target(|target| {
+            assert_eq!(selected_target(), target);
+            assert_eq!(v.iter().position_eq_simd(&6), Some(6));
+            assert_eq!(v.iter().count_eq_simd(&3), 143);
+            assert_eq!(v.iter().position_simd(|x| *x == 6), Some(6));
+            assert_eq!(v.iter().sum_simd(), 2997);
+        });
+        let _lock = lock_target();
+        assert_eq!(
+            selected_target(),
+            target_from_env()
+                .ok()
+                .flatten()
+                .unwrap_or_else(detected_target)
+        );
+    }
+
+    #[test]
+    fn test_target_byte_roundtrip() {
+        for target in TARGETS {
+            assert_eq!(from_byte(target as u8), target);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_supported_target() {
+        assert_eq!(supported_target("generic"), Ok(Target::Generic));
+        assert_eq!(
+            supported_target("avx"),
+            Err(TargetError::Unknown(ParseTargetError))
+        );
+        if let Some(unsupported) = TARGETS.into_iter().find(|target| !target.is_supported()) {
+            assert_eq!(
+                supported_target(unsupported.name()),
+                Err(TargetError::Unsupported(unsupported))
+            );
+            assert_eq!(
+                try_force_target(unsupported),
+                Err(TargetError::Unsupported(unsupported))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_force_unsupported_target() {
+        let unsupported = TARGETS.into_iter().find(|target| !target.is_supported());
+        force_target(unsupported.unwrap());
+    }
+}
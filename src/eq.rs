@@ -0,0 +1,138 @@
+use crate::count::count_simd_internal;
+use crate::position::position_autovec;
+#[cfg(target_arch = "x86_64")]
+use crate::x86::{count_with, position_with, Level};
+use core::slice;
+
+/// Types `EqSimd` can search for. On x86_64 the searches use hand-written
+/// SSE2, AVX2 or, with the `avx512` feature, AVX-512BW kernels as picked by
+/// `dispatch::selected_target`, elsewhere they use the autovectorized kernels.
+///
+/// Sealed, since the x86_64 kernels only know the lane layout of these types.
+pub trait EqElement: sealed::Sealed + Copy + PartialEq {
+    #[doc(hidden)]
+    fn position_eq(v: &[Self], needle: Self) -> Option<usize>;
+    #[doc(hidden)]
+    fn count_eq(v: &[Self], needle: Self) -> usize;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_eq_element {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl EqElement for $t {
+                fn position_eq(v: &[Self], needle: Self) -> Option<usize> {
+                    #[cfg(target_arch = "x86_64")]
+                    if let Some(level) = Level::selected() {
+                        return position_with(level, v, needle);
+                    }
+                    position_autovec(v, |x| *x == needle)
+                }
+                fn count_eq(v: &[Self], needle: Self) -> usize {
+                    #[cfg(target_arch = "x86_64")]
+                    if let Some(level) = Level::selected() {
+                        return count_with(level, v, needle);
+                    }
+                    count_simd_internal(v, |x| *x == needle)
+                }
+            }
+        )*
+    };
+}
+
+impl_eq_element!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Searches for a single value of a primitive type. Same results as
+/// `contains_simd`, `position_simd(|x| *x == elem)` and
+/// `count_simd(|x| *x == elem)`, so NaN is never found.
+pub trait EqSimd<'a, T>
+where
+    T: EqElement,
+{
+    fn contains_eq_simd(&self, elem: &T) -> bool;
+    fn position_eq_simd(&self, elem: &T) -> Option<usize>;
+    fn count_eq_simd(&self, elem: &T) -> usize;
+}
+
+impl<'a, T> EqSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: EqElement,
+{
+    fn contains_eq_simd(&self, elem: &T) -> bool {
+        T::position_eq(self.as_slice(), *elem).is_some()
+    }
+    fn position_eq_simd(&self, elem: &T) -> Option<usize> {
+        T::position_eq(self.as_slice(), *elem)
+    }
+    fn count_eq_simd(&self, elem: &T) -> usize {
+        T::count_eq(self.as_slice(), *elem)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatch::tests::for_each_target;
+    use crate::{ContainsSimd, CountSimd, PositionSimd};
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: EqElement + Default + Debug,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..500 {
+            let mut v: Vec<T> = vec![T::default(); len];
+            for x in v.iter_mut() {
+                *x = rng.gen()
+            }
+            let needle = match len {
+                0 => T::default(),
+                _ => v[rng.gen_range(0..len)],
+            };
+            for needle in [needle, rng.gen()] {
+                assert_eq!(
+                    v.iter().position_eq_simd(&needle),
+                    v.iter().position_simd(|x| *x == needle),
+                    "Failed for length {} and type {:?}",
+                    len,
+                    std::any::type_name::<T>()
+                );
+                assert_eq!(
+                    v.iter().contains_eq_simd(&needle),
+                    v.iter().contains_simd(&needle)
+                );
+                assert_eq!(
+                    v.iter().count_eq_simd(&needle),
+                    v.iter().count_simd(|x| *x == needle)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_eq() {
+        for_each_target(|_| {
+            test_simd_for_type::<i8>();
+            test_simd_for_type::<i16>();
+            test_simd_for_type::<i32>();
+            test_simd_for_type::<i64>();
+            test_simd_for_type::<u8>();
+            test_simd_for_type::<u16>();
+            test_simd_for_type::<u32>();
+            test_simd_for_type::<u64>();
+            test_simd_for_type::<usize>();
+            test_simd_for_type::<isize>();
+            test_simd_for_type::<f32>();
+            test_simd_for_type::<f64>();
+        });
+    }
+}
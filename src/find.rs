@@ -1,9 +1,9 @@
 use crate::position::PositionSimd;
-use std::slice;
+use core::slice;
 
 pub trait FindSimd<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn find_simd<F>(&self, f: F) -> Option<&'a T>
     where
@@ -12,7 +12,7 @@ where
 
 impl<'a, T> FindSimd<'a, T> for slice::Iter<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn find_simd<F>(&self, f: F) -> Option<&'a T>
     where
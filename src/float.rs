@@ -0,0 +1,135 @@
+use core::cmp::Ordering;
+use core::ops::{Add, Sub};
+
+/// How float reductions treat NaN.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// NaNs are skipped. If every element is NaN there is no result.
+    Ignore,
+    /// The first NaN wins over every other value.
+    Propagate,
+    /// Elements are ordered by `total_cmp`, so positive NaNs are greater than
+    /// `inf` and negative NaNs are less than `-inf`.
+    TotalOrder,
+}
+
+// `|x, best|` comparators, true when `x` replaces `best` as the minimum or
+// the maximum. With `Ignore` a NaN only stays the best if every element is
+// NaN, with `Propagate` the first NaN always does.
+impl NanPolicy {
+    #[inline(always)]
+    pub(crate) fn ignore_less<T: SimdFloat>(x: &T, best: &T) -> bool {
+        x < best || (best.is_nan() && !x.is_nan())
+    }
+    #[inline(always)]
+    pub(crate) fn ignore_greater<T: SimdFloat>(x: &T, best: &T) -> bool {
+        x > best || (best.is_nan() && !x.is_nan())
+    }
+    #[inline(always)]
+    pub(crate) fn propagate_less<T: SimdFloat>(x: &T, best: &T) -> bool {
+        x < best || (x.is_nan() && !best.is_nan())
+    }
+    #[inline(always)]
+    pub(crate) fn propagate_greater<T: SimdFloat>(x: &T, best: &T) -> bool {
+        x > best || (x.is_nan() && !best.is_nan())
+    }
+    #[inline(always)]
+    pub(crate) fn total_order_less<T: SimdFloat>(x: &T, best: &T) -> bool {
+        x.total_cmp(best) == Ordering::Less
+    }
+    #[inline(always)]
+    pub(crate) fn total_order_greater<T: SimdFloat>(x: &T, best: &T) -> bool {
+        x.total_cmp(best) == Ordering::Greater
+    }
+}
+
+/// Evaluates `$body` with `$less` and `$greater` bound to the comparators
+/// of `$policy` for elements of type `$t`.
+///
+/// Expands the body once per policy, so every kernel is compiled for one
+/// comparator instead of branching on the policy for every element.
+macro_rules! with_nan_cmp {
+    ($policy:expr, $t:ty, |$less:ident, $greater:ident| $body:expr) => {
+        match $policy {
+            crate::float::NanPolicy::Ignore => {
+                let $less = crate::float::NanPolicy::ignore_less::<$t>;
+                let $greater = crate::float::NanPolicy::ignore_greater::<$t>;
+                $body
+            }
+            crate::float::NanPolicy::Propagate => {
+                let $less = crate::float::NanPolicy::propagate_less::<$t>;
+                let $greater = crate::float::NanPolicy::propagate_greater::<$t>;
+                $body
+            }
+            crate::float::NanPolicy::TotalOrder => {
+                let $less = crate::float::NanPolicy::total_order_less::<$t>;
+                let $greater = crate::float::NanPolicy::total_order_greater::<$t>;
+                $body
+            }
+        }
+    };
+}
+pub(crate) use with_nan_cmp;
+
+/// How float sums are accumulated.
+///
+/// Every mode adds the elements in a fixed order that does not depend on
+/// which instruction set was picked at runtime, so the result is the same
+/// on every target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SumMode {
+    /// `LANE_COUNT` independent accumulators combined pairwise at the end.
+    Lanes,
+    /// Like `Lanes` but every accumulator carries a Neumaier compensation
+    /// term. Slower, but the error does not grow with the length.
+    Compensated,
+    /// Blocks summed with `Lanes` are combined pairwise, so the error grows
+    /// with the logarithm of the length.
+    Pairwise,
+}
+
+/// Float types accepted by the float reductions.
+///
+/// Sealed, so that more float types can be added without a breaking change.
+pub trait SimdFloat:
+    sealed::Sealed + Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self>
+{
+    const ZERO: Self;
+    fn is_nan(self) -> bool;
+    fn is_finite(self) -> bool;
+    fn abs(self) -> Self;
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_simd_float {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl SimdFloat for $t {
+                const ZERO: Self = 0.0;
+                #[inline(always)]
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+                #[inline(always)]
+                fn is_finite(self) -> bool {
+                    <$t>::is_finite(self)
+                }
+                #[inline(always)]
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+                #[inline(always)]
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    <$t>::total_cmp(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_simd_float!(f32, f64);
@@ -0,0 +1,132 @@
+use crate::dispatch::simd_kernel;
+use crate::LANE_COUNT;
+use core::slice;
+
+fn fold_simd_internal<T, A, F, C>(v: &[T], identity: A, lane_op: F, combine: C) -> A
+where
+    A: Copy,
+    F: Fn(A, &T) -> A,
+    C: Fn(A, A) -> A,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut acc = [identity; LANE_COUNT];
+        for chunk in chunks.by_ref() {
+            for (a, x) in acc.iter_mut().zip(chunk) {
+                *a = lane_op(*a, x);
+            }
+        }
+        for (a, x) in acc.iter_mut().zip(chunks.remainder()) {
+            *a = lane_op(*a, x);
+        }
+        acc.into_iter().fold(identity, combine)
+    }
+}
+
+pub trait FoldSimd<'a, T> {
+    fn fold_simd<A, F, C>(&self, identity: A, lane_op: F, combine: C) -> A
+    where
+        A: Copy,
+        F: Fn(A, &T) -> A,
+        C: Fn(A, A) -> A;
+}
+
+impl<'a, T> FoldSimd<'a, T> for slice::Iter<'a, T> {
+    /// Runs `LANE_COUNT` independent accumulators, each starting at
+    /// `identity` and fed with `lane_op`, and merges them with `combine`.
+    ///
+    /// The elements are not visited in order, so this only matches
+    /// `Iterator::fold` if `combine` is associative and commutative,
+    /// `identity` is its identity element and
+    /// `combine(a, lane_op(b, x)) == lane_op(combine(a, b), x)`.
+    ///
+    /// ```
+    /// use simd_itertools::FoldSimd;
+    ///
+    /// let flags = [0b0001u32, 0b0100, 0b0001];
+    /// let any_flags = flags.iter().fold_simd(0, |acc, x| acc | x, |a, b| a | b);
+    /// assert_eq!(any_flags, 0b0101);
+    /// ```
+    fn fold_simd<A, F, C>(&self, identity: A, lane_op: F, combine: C) -> A
+    where
+        A: Copy,
+        F: Fn(A, &T) -> A,
+        C: Fn(A, A) -> A,
+    {
+        fold_simd_internal(self.as_slice(), identity, lane_op, combine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+    use std::ops::{BitAnd, BitOr};
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + Debug
+            + Ord
+            + BitOr<Output = T>
+            + BitAnd<Output = T>,
+        Standard: Distribution<T>,
+    {
+        for len in 0..5000 {
+            let mut v: Vec<T> = vec![T::default(); len];
+            let mut rng = rand::thread_rng();
+            for x in v.iter_mut() {
+                *x = rng.gen()
+            }
+            let ones = v.iter().fold(T::default(), |a, b| a | *b);
+            let zeros = v.iter().fold(ones, |a, b| a & *b);
+
+            let or = v
+                .iter()
+                .fold_simd(T::default(), |a, b| a | *b, |a, b| a | b);
+            let and = v.iter().fold_simd(ones, |a, b| a & *b, |a, b| a & b);
+            let max = v
+                .iter()
+                .fold_simd(None, |a, b| a.max(Some(*b)), |a, b| a.max(b));
+            let count =
+                v.iter()
+                    .fold_simd(0, |a, b| a + (*b > T::default()) as usize, |a, b| a + b);
+            assert_eq!(
+                or,
+                ones,
+                "Failed for length {} and type {:?}",
+                len,
+                std::any::type_name::<T>()
+            );
+            assert_eq!(
+                and,
+                zeros,
+                "Failed for length {} and type {:?}",
+                len,
+                std::any::type_name::<T>()
+            );
+            assert_eq!(max, v.iter().copied().max());
+            assert_eq!(count, v.iter().filter(|x| **x > T::default()).count());
+        }
+    }
+
+    #[test]
+    fn test_simd_fold() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+}
@@ -0,0 +1,103 @@
+/// Integer types accepted by the integer reductions.
+///
+/// Sealed, as the reductions are only written for the primitive integers.
+pub trait SimdInt: sealed::Sealed + Copy + Ord {
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_mul(self, rhs: Self) -> Self;
+    /// Wrapping add that also reports whether the result wrapped past
+    /// `MAX` (`1`), past `MIN` (`-1`) or not at all (`0`).
+    fn carrying_add(self, rhs: Self) -> (Self, i64);
+    fn overflowing_mul(self, rhs: Self) -> (Self, bool);
+    fn is_negative(self) -> bool;
+    fn unsigned_abs_u128(self) -> u128;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_simd_int_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl SimdInt for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+                #[inline(always)]
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+                #[inline(always)]
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+                #[inline(always)]
+                fn carrying_add(self, rhs: Self) -> (Self, i64) {
+                    let (sum, overflow) = <$t>::overflowing_add(self, rhs);
+                    (sum, overflow as i64)
+                }
+                #[inline(always)]
+                fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+                    <$t>::overflowing_mul(self, rhs)
+                }
+                #[inline(always)]
+                fn is_negative(self) -> bool {
+                    false
+                }
+                #[inline(always)]
+                fn unsigned_abs_u128(self) -> u128 {
+                    self as u128
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_simd_int_signed {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+            impl SimdInt for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+                #[inline(always)]
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+                #[inline(always)]
+                fn wrapping_mul(self, rhs: Self) -> Self {
+                    <$t>::wrapping_mul(self, rhs)
+                }
+                #[inline(always)]
+                fn carrying_add(self, rhs: Self) -> (Self, i64) {
+                    // A signed add can only wrap in the direction of `rhs`.
+                    let (sum, overflow) = <$t>::overflowing_add(self, rhs);
+                    (sum, overflow as i64 * (1 - 2 * (rhs < 0) as i64))
+                }
+                #[inline(always)]
+                fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+                    <$t>::overflowing_mul(self, rhs)
+                }
+                #[inline(always)]
+                fn is_negative(self) -> bool {
+                    self < 0
+                }
+                #[inline(always)]
+                fn unsigned_abs_u128(self) -> u128 {
+                    self.unsigned_abs() as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_simd_int_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_simd_int_signed!(i8, i16, i32, i64, i128, isize);
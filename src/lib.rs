@@ -1,20 +1,82 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "portable_simd", feature(portable_simd))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub const LANE_COUNT: usize = 32;
 pub const UNROLL_FACTOR: usize = 4;
 
 mod all;
 mod any;
+mod argbest;
 mod argmax;
 mod argmin;
+mod argminmax;
 mod contains;
+mod count;
+/// Which instruction set the kernels run with, and overriding it with
+/// `force_target` or `SIMD_ITERTOOLS_TARGET`.
+pub mod dispatch;
+mod eq;
 mod filter;
 mod find;
+mod float;
+mod fold;
+mod int;
+mod mask;
+mod minmax;
+#[cfg(feature = "rayon")]
+mod par;
+mod partition;
+#[cfg(feature = "alloc")]
+mod per_chunk;
+#[cfg(feature = "portable_simd")]
+mod portable;
 mod position;
+mod replace;
+#[cfg(feature = "alloc")]
+mod retain;
+mod rfind;
+mod rposition;
+mod slice_simd;
+mod sum;
+#[cfg(feature = "alloc")]
+mod vec_deque;
+#[cfg(target_arch = "x86_64")]
+mod x86;
 
 pub use all::AllSimd;
 pub use any::AnySimd;
-pub use argmax::ArgmaxSimd;
-pub use argmin::ArgminSimd;
+pub use argmax::{ArgmaxFloatSimd, ArgmaxSimd};
+pub use argmin::{ArgminFloatSimd, ArgminSimd};
+pub use argminmax::{ArgMinMaxFloatSimd, ArgMinMaxSimd};
 pub use contains::ContainsSimd;
-pub use filter::FilterSimd;
+pub use count::CountSimd;
+pub use eq::{EqElement, EqSimd};
+pub use filter::{FilterSimd, SimdFilter, SimdFilterIndices};
 pub use find::FindSimd;
+pub use float::{NanPolicy, SimdFloat, SumMode};
+pub use fold::FoldSimd;
+pub use int::SimdInt;
+pub use mask::MaskSimd;
+pub use minmax::{MinMaxFloatSimd, MinMaxSimd};
+#[cfg(feature = "rayon")]
+pub use par::ParSimd;
+pub use partition::PartitionInPlaceSimd;
+#[cfg(feature = "alloc")]
+pub use partition::PartitionSimd;
+#[cfg(feature = "alloc")]
+pub use per_chunk::PerChunkSimd;
+#[cfg(feature = "portable_simd")]
+pub use portable::{Cmp, PortableElement, PortableOrd, PortableSimd};
 pub use position::PositionSimd;
+pub use replace::ReplaceSimd;
+#[cfg(feature = "alloc")]
+pub use retain::RetainSimd;
+pub use rfind::RFindSimd;
+pub use rposition::RPositionSimd;
+pub use slice_simd::SliceSimd;
+pub use sum::{FloatSumSimd, SumSimd};
+#[cfg(feature = "alloc")]
+pub use vec_deque::VecDequeSimd;
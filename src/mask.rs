@@ -0,0 +1,197 @@
+use crate::dispatch::simd_kernel;
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::slice;
+
+/// Elements per mask word.
+pub(crate) const WORD_BITS: usize = u64::BITS as usize;
+
+/// Packs `f` of up to `WORD_BITS` elements into one word.
+#[inline(always)]
+pub(crate) fn mask_word<F, T>(chunk: &[T], f: &F) -> u64
+where
+    F: Fn(&T) -> bool,
+{
+    match <&[T; WORD_BITS]>::try_from(chunk) {
+        Ok(chunk) => {
+            let mut bytes = [0u8; WORD_BITS];
+            for (byte, x) in bytes.iter_mut().zip(chunk) {
+                *byte = f(x) as u8;
+            }
+            pack_bytes(&bytes)
+        }
+        Err(_) => chunk
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, x)| acc | ((f(x) as u64) << i)),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn pack_bytes(bytes: &[u8; WORD_BITS]) -> u64 {
+    use core::arch::x86_64::*;
+    let mut mask = 0;
+    for (i, group) in bytes.chunks_exact(16).enumerate() {
+        // SAFETY: `group` is 16 bytes long, and SSE2 is always there on x86_64.
+        let bits = unsafe {
+            let v = _mm_loadu_si128(group.as_ptr().cast());
+            _mm_movemask_epi8(_mm_slli_epi16::<7>(v))
+        };
+        mask |= (bits as u16 as u64) << (16 * i);
+    }
+    mask
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+#[inline(always)]
+fn pack_bytes(bytes: &[u8; WORD_BITS]) -> u64 {
+    bytes
+        .iter()
+        .enumerate()
+        .fold(0, |acc, (i, byte)| acc | ((*byte as u64) << i))
+}
+
+pub(crate) fn mask_simd_internal<F, T>(v: &[T], mask: &mut [u64], f: F)
+where
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        for (chunk, word) in v.chunks(WORD_BITS).zip(mask.iter_mut()) {
+            *word = mask_word(chunk, &f);
+        }
+    }
+}
+
+pub trait MaskSimd<'a, T> {
+    #[cfg(feature = "alloc")]
+    fn mask_simd<F>(&self, f: F) -> Vec<u64>
+    where
+        F: Fn(&T) -> bool;
+    fn mask_into_simd<F>(&self, mask: &mut [u64], f: F)
+    where
+        F: Fn(&T) -> bool;
+}
+
+impl<'a, T> MaskSimd<'a, T> for slice::Iter<'a, T> {
+    /// Evaluates `f` on every element and packs the results into a bitmask.
+    /// Element `i` is bit `i % 64` of word `i / 64`, unused bits of the last
+    /// word are zero.
+    ///
+    /// Masks of the same slice can be combined with plain bitwise ops:
+    /// ```
+    /// use simd_itertools::MaskSimd;
+    ///
+    /// let v = [1, 5, 10, 15, 20];
+    /// let big = v.iter().mask_simd(|x| *x > 4);
+    /// let even = v.iter().mask_simd(|x| *x % 2 == 0);
+    /// let both: u32 = big.iter().zip(&even).map(|(a, b)| (a & b).count_ones()).sum();
+    /// assert_eq!(both, 2);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn mask_simd<F>(&self, f: F) -> Vec<u64>
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut mask = vec![0; self.len().div_ceil(WORD_BITS)];
+        mask_simd_internal(self.as_slice(), &mut mask, f);
+        mask
+    }
+    /// Same as `mask_simd` but writes into `mask`.
+    ///
+    /// Panics if `mask` is shorter than `len.div_ceil(64)` words. Words past
+    /// that are left untouched.
+    fn mask_into_simd<F>(&self, mask: &mut [u64], f: F)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let words = self.len().div_ceil(WORD_BITS);
+        assert!(
+            mask.len() >= words,
+            "mask has {} words but {} are needed",
+            mask.len(),
+            words
+        );
+        mask_simd_internal(self.as_slice(), &mut mask[..words], f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + std::cmp::PartialEq
+            + std::cmp::PartialOrd,
+        Standard: Distribution<T>,
+    {
+        for len in 0..2000 {
+            let ops = [
+                |x: &T| *x == T::default(),
+                |x: &T| *x != T::default(),
+                |x: &T| *x < T::default(),
+                |x: &T| *x > T::default(),
+                |x: &T| [T::default()].contains(x),
+            ];
+
+            for op in ops {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+
+                let mut correct = vec![0u64; len.div_ceil(64)];
+                for (i, x) in v.iter().enumerate() {
+                    correct[i / 64] |= (op(x) as u64) << (i % 64);
+                }
+                #[cfg(feature = "alloc")]
+                {
+                    let ans = v.iter().mask_simd(op);
+                    assert_eq!(
+                        ans,
+                        correct,
+                        "Failed for length {} and type {:?}",
+                        len,
+                        std::any::type_name::<T>()
+                    );
+                }
+                let mut ans = vec![u64::MAX; correct.len() + 1];
+                v.iter().mask_into_simd(&mut ans, op);
+                assert_eq!(ans[..correct.len()], correct);
+                assert_eq!(ans[correct.len()], u64::MAX);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_mask() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_simd_mask_into_too_short() {
+        let v = [0u8; 65];
+        v.iter().mask_into_simd(&mut [0u64; 1], |x| *x == 0);
+    }
+}
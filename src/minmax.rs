@@ -0,0 +1,278 @@
+use crate::dispatch::simd_kernel;
+use crate::float::{with_nan_cmp, NanPolicy, SimdFloat};
+use crate::LANE_COUNT;
+use core::slice;
+
+fn best_simd_internal<T, F>(v: &[T], is_better: F) -> Option<T>
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+{
+    // Nothing is ever better under the second ordering, so once inlined
+    // its lanes never change and compile away.
+    best2_simd_internal(v, is_better, |_: &T, _: &T| false).map(|(best, _)| best)
+}
+
+fn best2_simd_internal<T, F, G>(v: &[T], is_better_a: F, is_better_b: G) -> Option<(T, T)>
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+    G: Fn(&T, &T) -> bool,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut best = match chunks.next() {
+            Some(first) => {
+                let mut vals_a: [T; LANE_COUNT] = first.try_into().unwrap();
+                let mut vals_b: [T; LANE_COUNT] = vals_a;
+                for chunk in chunks.by_ref() {
+                    for ((a, b), x) in vals_a.iter_mut().zip(vals_b.iter_mut()).zip(chunk) {
+                        *a = if is_better_a(x, a) { *x } else { *a };
+                        *b = if is_better_b(x, b) { *x } else { *b };
+                    }
+                }
+                Some((reduce_lanes(vals_a, &is_better_a), reduce_lanes(vals_b, &is_better_b)))
+            }
+            None => None,
+        };
+        for x in chunks.remainder() {
+            best = Some(match best {
+                Some((a, b)) => (
+                    if is_better_a(x, &a) { *x } else { a },
+                    if is_better_b(x, &b) { *x } else { b },
+                ),
+                None => (*x, *x),
+            });
+        }
+        best
+    }
+}
+
+#[inline(always)]
+fn reduce_lanes<T, F>(vals: [T; LANE_COUNT], is_better: &F) -> T
+where
+    T: Copy,
+    F: Fn(&T, &T) -> bool,
+{
+    vals.into_iter()
+        .reduce(|a, b| if is_better(&b, &a) { b } else { a })
+        .unwrap()
+}
+
+pub trait MinMaxSimd<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    fn min_simd(&self) -> Option<T>;
+    fn max_simd(&self) -> Option<T>;
+    fn minmax_simd(&self) -> Option<(T, T)>;
+}
+
+impl<'a, T> MinMaxSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq + core::cmp::PartialOrd + Copy + core::cmp::Ord,
+{
+    fn min_simd(&self) -> Option<T> {
+        best_simd_internal(self.as_slice(), |x, min| x < min)
+    }
+    fn max_simd(&self) -> Option<T> {
+        best_simd_internal(self.as_slice(), |x, max| x > max)
+    }
+    /// Minimum and maximum in one pass.
+    fn minmax_simd(&self) -> Option<(T, T)> {
+        best2_simd_internal(self.as_slice(), |x, min| x < min, |x, max| x > max)
+    }
+}
+
+/// With `NanPolicy::Ignore` and `NanPolicy::Propagate` it is unspecified
+/// which of `0.0` and `-0.0`, or which NaN, is returned when several compare
+/// equal. `NanPolicy::TotalOrder` always returns a bit-exact result.
+pub trait MinMaxFloatSimd<'a, T>
+where
+    T: SimdFloat,
+{
+    fn min_float_simd(&self, policy: NanPolicy) -> Option<T>;
+    fn max_float_simd(&self, policy: NanPolicy) -> Option<T>;
+    fn minmax_float_simd(&self, policy: NanPolicy) -> Option<(T, T)>;
+}
+
+impl<'a, T> MinMaxFloatSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: SimdFloat,
+{
+    fn min_float_simd(&self, policy: NanPolicy) -> Option<T> {
+        let min = with_nan_cmp!(policy, T, |is_less, _is_greater| {
+            best_simd_internal(self.as_slice(), is_less)
+        });
+        match policy {
+            NanPolicy::Ignore => min.filter(|min| !min.is_nan()),
+            _ => min,
+        }
+    }
+    fn max_float_simd(&self, policy: NanPolicy) -> Option<T> {
+        let max = with_nan_cmp!(policy, T, |_is_less, is_greater| {
+            best_simd_internal(self.as_slice(), is_greater)
+        });
+        match policy {
+            NanPolicy::Ignore => max.filter(|max| !max.is_nan()),
+            _ => max,
+        }
+    }
+    fn minmax_float_simd(&self, policy: NanPolicy) -> Option<(T, T)> {
+        let minmax = with_nan_cmp!(policy, T, |is_less, is_greater| {
+            best2_simd_internal(self.as_slice(), is_less, is_greater)
+        });
+        match policy {
+            NanPolicy::Ignore => minmax.filter(|(min, _)| !min.is_nan()),
+            _ => minmax,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cmp::Ordering;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Debug
+            + Copy
+            + Default
+            + std::cmp::PartialEq
+            + Ord,
+        Standard: Distribution<T>,
+    {
+        for len in 0..1000 {
+            for _ in 0..5 {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+                let correct_min = v.iter().copied().min();
+                let correct_max = v.iter().copied().max();
+                assert_eq!(v.iter().min_simd(), correct_min, "{:?}", v);
+                assert_eq!(v.iter().max_simd(), correct_max, "{:?}", v);
+                assert_eq!(
+                    v.iter().minmax_simd(),
+                    correct_min.zip(correct_max),
+                    "Failed for length {} and type {:?} {:?}",
+                    len,
+                    std::any::type_name::<T>(),
+                    v
+                );
+            }
+        }
+    }
+
+    // -0.0 == 0.0 and NaN != NaN, so compare the way the policy promises.
+    fn same_value<T: SimdFloat>(a: Option<T>, b: Option<T>, policy: NanPolicy) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) if policy == NanPolicy::TotalOrder => a.total_cmp(&b).is_eq(),
+            (Some(a), Some(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // The first element that is `want` compared to every other one, with
+    // NaNs treated as the policy says.
+    fn best_float_reference<T: SimdFloat>(v: &[T], policy: NanPolicy, want: Ordering) -> Option<T> {
+        let best = v
+            .iter()
+            .enumerate()
+            .fold(None, |best: Option<usize>, (i, x)| {
+                let better = match (policy, best) {
+                    (NanPolicy::Propagate, Some(b)) if v[b].is_nan() => false,
+                    (NanPolicy::Propagate, _) if x.is_nan() => true,
+                    (NanPolicy::TotalOrder, None) => true,
+                    (_, None) => !x.is_nan(),
+                    (NanPolicy::TotalOrder, Some(b)) => x.total_cmp(&v[b]) == want,
+                    (_, Some(b)) => x.partial_cmp(&v[b]) == Some(want),
+                };
+                if better {
+                    Some(i)
+                } else {
+                    best
+                }
+            });
+        best.map(|i| v[i])
+    }
+
+    fn test_float_for_type<T>()
+    where
+        T: SimdFloat + From<f32> + Debug,
+    {
+        let specials = [0.0, -0.0, f32::INFINITY, f32::NEG_INFINITY];
+        let mut rng = rand::thread_rng();
+        for len in 0..1000 {
+            for nan_chance in [0.0, 0.01, 0.5, 1.0] {
+                let v: Vec<T> = (0..len)
+                    .map(|_| {
+                        if rng.gen_bool(nan_chance) {
+                            T::from(if rng.gen() { f32::NAN } else { -f32::NAN })
+                        } else if rng.gen_bool(0.1) {
+                            T::from(specials[rng.gen_range(0..specials.len())])
+                        } else {
+                            T::from(rng.gen_range(-100..100) as f32)
+                        }
+                    })
+                    .collect();
+                for policy in [
+                    NanPolicy::Ignore,
+                    NanPolicy::Propagate,
+                    NanPolicy::TotalOrder,
+                ] {
+                    let correct_min = best_float_reference(&v, policy, Ordering::Less);
+                    let correct_max = best_float_reference(&v, policy, Ordering::Greater);
+                    let (min, max) = match v.iter().minmax_float_simd(policy) {
+                        Some((min, max)) => (Some(min), Some(max)),
+                        None => (None, None),
+                    };
+                    for (ans, correct) in [
+                        (v.iter().min_float_simd(policy), correct_min),
+                        (v.iter().max_float_simd(policy), correct_max),
+                        (min, correct_min),
+                        (max, correct_max),
+                    ] {
+                        assert!(
+                            same_value(ans, correct, policy),
+                            "Failed for length {}, policy {:?} and type {:?} {:?}",
+                            len,
+                            policy,
+                            std::any::type_name::<T>(),
+                            v
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_minmax_values() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+
+    #[test]
+    fn test_simd_minmax_values_float() {
+        test_float_for_type::<f32>();
+        test_float_for_type::<f64>();
+    }
+}
@@ -0,0 +1,237 @@
+use crate::all::all_simd_internal;
+use crate::any::any_simd_internal;
+use crate::argbest::argbest_autovec;
+use crate::count::count_simd_internal;
+use crate::int::SimdInt;
+use crate::position::position_autovec;
+use crate::sum::wrapping_sum_internal;
+use core::slice;
+use rayon::prelude::*;
+
+/// Bytes handed to one task, small enough that a block stays in L2.
+const BLOCK_BYTES: usize = 64 * 1024;
+
+fn block_len<T>() -> usize {
+    (BLOCK_BYTES / core::mem::size_of::<T>().max(1)).max(1)
+}
+
+/// Index of the first element that no other element `is_better` than.
+fn argbest_par<T, F>(v: &[T], is_better: F) -> Option<usize>
+where
+    T: Copy + Sync,
+    F: Fn(&T, &T) -> bool + Sync + Send,
+{
+    let block = block_len::<T>();
+    v.par_chunks(block)
+        .enumerate()
+        .map(|(i, chunk)| {
+            // Blocks are never empty.
+            let idx = argbest_autovec(chunk, &is_better).unwrap();
+            (i * block + idx, &chunk[idx])
+        })
+        // Blocks are combined in order, so keeping the left one on ties
+        // gives the first occurrence.
+        .reduce_with(|a, b| if is_better(b.1, a.1) { b } else { a })
+        .map(|(idx, _)| idx)
+}
+
+/// Parallel versions of the reductions, splitting the slice into cache-sized
+/// blocks that each run the SIMD kernel. `any`, `all` and `position` stop
+/// scheduling blocks once the answer is known.
+pub trait ParSimd<'a, T>
+where
+    T: core::cmp::PartialEq + Sync,
+{
+    fn par_any_simd<F>(&self, f: F) -> bool
+    where
+        F: Fn(&T) -> bool + Sync + Send;
+    fn par_all_simd<F>(&self, f: F) -> bool
+    where
+        F: Fn(&T) -> bool + Sync + Send;
+    /// Index of the first match in the whole slice, not just the first found.
+    fn par_position_simd<F>(&self, f: F) -> Option<usize>
+    where
+        F: Fn(&T) -> bool + Sync + Send;
+    fn par_count_simd<F>(&self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool + Sync + Send;
+    fn par_argmax_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    fn par_argmin_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    /// Wrapping sum, same as `sum_simd`.
+    fn par_sum_simd(&self) -> T
+    where
+        T: SimdInt + Send;
+}
+
+impl<'a, T> ParSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq + Sync,
+{
+    fn par_any_simd<F>(&self, f: F) -> bool
+    where
+        F: Fn(&T) -> bool + Sync + Send,
+    {
+        self.as_slice()
+            .par_chunks(block_len::<T>())
+            .any(|chunk| any_simd_internal(chunk, &f))
+    }
+    fn par_all_simd<F>(&self, f: F) -> bool
+    where
+        F: Fn(&T) -> bool + Sync + Send,
+    {
+        self.as_slice()
+            .par_chunks(block_len::<T>())
+            .all(|chunk| all_simd_internal(chunk, &f))
+    }
+    fn par_position_simd<F>(&self, f: F) -> Option<usize>
+    where
+        F: Fn(&T) -> bool + Sync + Send,
+    {
+        let block = block_len::<T>();
+        self.as_slice()
+            .par_chunks(block)
+            .enumerate()
+            .find_map_first(|(i, chunk)| position_autovec(chunk, &f).map(|idx| i * block + idx))
+    }
+    fn par_count_simd<F>(&self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool + Sync + Send,
+    {
+        self.as_slice()
+            .par_chunks(block_len::<T>())
+            .map(|chunk| count_simd_internal(chunk, &f))
+            .sum()
+    }
+    fn par_argmax_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        argbest_par(self.as_slice(), |x, max| x > max)
+    }
+    fn par_argmin_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        argbest_par(self.as_slice(), |x, min| x < min)
+    }
+    fn par_sum_simd(&self) -> T
+    where
+        T: SimdInt + Send,
+    {
+        self.as_slice()
+            .par_chunks(block_len::<T>())
+            .map(wrapping_sum_internal::<T, T>)
+            .reduce(|| T::ZERO, T::wrapping_add)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn check_search<T>(v: &[T], needle: T)
+    where
+        T: PartialEq + Copy + Debug + Sync,
+    {
+        let op = |x: &T| *x == needle;
+        let op_all = |x: &T| *x != needle;
+        assert_eq!(
+            v.iter().par_any_simd(op),
+            v.iter().any(op),
+            "Failed for length {} and type {:?}",
+            v.len(),
+            std::any::type_name::<T>()
+        );
+        assert_eq!(v.iter().par_all_simd(op_all), v.iter().all(op_all));
+        assert_eq!(v.iter().par_position_simd(op), v.iter().position(op));
+        assert_eq!(
+            v.iter().par_count_simd(op),
+            v.iter().filter(|x| op(x)).count()
+        );
+    }
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + Debug
+            + Ord
+            + Sync
+            + Send
+            + SimdInt,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        // Long enough to span several blocks even for u8.
+        for _ in 0..20 {
+            let len = rng.gen_range(0..300_000);
+            let mut v: Vec<T> = vec![T::default(); len];
+            for x in v.iter_mut() {
+                *x = rng.gen_range(T::MIN..=T::MAX)
+            }
+            let needle = match v.is_empty() {
+                true => T::default(),
+                false => v[rng.gen_range(0..len)],
+            };
+            check_search(&v, needle);
+
+            // No match at all, so no block can stop the search early.
+            let other = if needle == T::MAX { T::MIN } else { T::MAX };
+            let mut absent: Vec<T> = v
+                .iter()
+                .map(|x| if *x == needle { other } else { *x })
+                .collect();
+            check_search(&absent, needle);
+            if len > 0 {
+                // The only match is in the last block.
+                absent[len - 1] = needle;
+                check_search(&absent, needle);
+                // An earlier match, usually in another block, has to win.
+                absent[rng.gen_range(0..len)] = needle;
+                check_search(&absent, needle);
+            }
+
+            let max = v.iter().max();
+            let min = v.iter().min();
+            assert_eq!(
+                v.iter().par_argmax_simd(),
+                max.and_then(|m| v.iter().position(|x| x == m)),
+            );
+            assert_eq!(
+                v.iter().par_argmin_simd(),
+                min.and_then(|m| v.iter().position(|x| x == m)),
+            );
+            assert_eq!(
+                v.iter().par_sum_simd(),
+                v.iter().fold(T::ZERO, |a, x| a.wrapping_add(*x)),
+                "Failed for length {} and type {:?}",
+                len,
+                std::any::type_name::<T>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_simd_par() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+}
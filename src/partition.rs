@@ -0,0 +1,242 @@
+use crate::dispatch::simd_kernel;
+use crate::filter::left_pack;
+use crate::mask::{mask_word, WORD_BITS};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::slice;
+
+#[cfg(feature = "alloc")]
+fn partition_into_internal<F, T>(
+    arr: &[T],
+    matching: &mut Vec<T>,
+    rest: &mut Vec<T>,
+    f: F,
+) -> (usize, usize)
+where
+    T: Copy,
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        let start_lens = (matching.len(), rest.len());
+        let mut offsets = [0; WORD_BITS];
+        for chunk in arr.chunks(WORD_BITS) {
+            let mask = mask_word(chunk, &f);
+            let len = left_pack(mask, &mut offsets);
+            matching.extend(offsets[..len].iter().map(|o| chunk[*o as usize]));
+            let len = left_pack(
+                !mask & (u64::MAX >> (WORD_BITS - chunk.len())),
+                &mut offsets,
+            );
+            rest.extend(offsets[..len].iter().map(|o| chunk[*o as usize]));
+        }
+        (matching.len() - start_lens.0, rest.len() - start_lens.1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub trait PartitionSimd<'a, T>
+where
+    T: core::cmp::PartialEq + Copy,
+{
+    fn partition_simd<F>(&self, f: F) -> (Vec<T>, Vec<T>)
+    where
+        F: Fn(&T) -> bool;
+    fn partition_into_simd<F>(
+        &self,
+        matching: &mut Vec<T>,
+        rest: &mut Vec<T>,
+        f: F,
+    ) -> (usize, usize)
+    where
+        F: Fn(&T) -> bool;
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T> PartitionSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq + Copy,
+{
+    /// Splits the elements into those matching `f` and the rest in a single
+    /// pass. Both keep their original order.
+    fn partition_simd<F>(&self, f: F) -> (Vec<T>, Vec<T>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let (mut matching, mut rest) = (vec![], vec![]);
+        partition_into_internal(self.as_slice(), &mut matching, &mut rest, f);
+        (matching, rest)
+    }
+    /// Same as `partition_simd` but appends to existing buffers. Returns how
+    /// many elements were appended to each.
+    fn partition_into_simd<F>(
+        &self,
+        matching: &mut Vec<T>,
+        rest: &mut Vec<T>,
+        f: F,
+    ) -> (usize, usize)
+    where
+        F: Fn(&T) -> bool,
+    {
+        partition_into_internal(self.as_slice(), matching, rest, f)
+    }
+}
+
+/// Hoare's partition on blocks of `WORD_BITS`: the misplaced elements of the
+/// leftmost and rightmost block are found with one mask each and swapped in
+/// pairs. The middle left when the blocks would overlap is done one element
+/// at a time.
+fn partition_in_place_internal<F, T>(v: &mut [T], f: F) -> usize
+where
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        // Everything before `lo` matches, everything from `hi` on does not.
+        let (mut lo, mut hi) = (0, v.len());
+        let (mut offsets_lo, mut offsets_hi) = ([0; WORD_BITS], [0; WORD_BITS]);
+        let (mut start_lo, mut len_lo) = (0, 0);
+        let (mut start_hi, mut len_hi) = (0, 0);
+        while hi - lo >= 2 * WORD_BITS {
+            if len_lo == 0 {
+                let mask = !mask_word(&v[lo..lo + WORD_BITS], &f);
+                (start_lo, len_lo) = (0, left_pack(mask, &mut offsets_lo));
+            }
+            if len_hi == 0 {
+                let mask = mask_word(&v[hi - WORD_BITS..hi], &f);
+                (start_hi, len_hi) = (0, left_pack(mask, &mut offsets_hi));
+            }
+            let n = len_lo.min(len_hi);
+            for (a, b) in offsets_lo[start_lo..start_lo + n]
+                .iter()
+                .zip(&offsets_hi[start_hi..start_hi + n])
+            {
+                v.swap(lo + *a as usize, hi - WORD_BITS + *b as usize);
+            }
+            (start_lo, len_lo) = (start_lo + n, len_lo - n);
+            (start_hi, len_hi) = (start_hi + n, len_hi - n);
+            if len_lo == 0 {
+                lo += WORD_BITS;
+            }
+            if len_hi == 0 {
+                hi -= WORD_BITS;
+            }
+        }
+        loop {
+            while lo < hi && f(&v[lo]) {
+                lo += 1;
+            }
+            while lo < hi && !f(&v[hi - 1]) {
+                hi -= 1;
+            }
+            if lo == hi {
+                return lo;
+            }
+            v.swap(lo, hi - 1);
+            lo += 1;
+            hi -= 1;
+        }
+    }
+}
+
+pub trait PartitionInPlaceSimd<T> {
+    fn partition_in_place_simd<F>(&mut self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool;
+}
+
+impl<T> PartitionInPlaceSimd<T> for [T] {
+    /// Reorders the slice so that the elements matching `f` come first and
+    /// returns how many there are. The order within each side is not kept.
+    ///
+    /// Works from both ends like Hoare's partition, a block of 64 elements
+    /// at a time, so `f` is called about once per element.
+    fn partition_in_place_simd<F>(&mut self, f: F) -> usize
+    where
+        F: Fn(&T) -> bool,
+    {
+        partition_in_place_internal(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + Debug
+            + std::cmp::PartialEq
+            + std::cmp::PartialOrd,
+        Standard: Distribution<T>,
+    {
+        for len in 0..2000 {
+            let ops = [
+                |x: &T| *x == T::default(),
+                |x: &T| *x != T::default(),
+                |x: &T| *x < T::default(),
+                |x: &T| *x > T::default(),
+                |x: &T| [T::default()].contains(x),
+            ];
+
+            for op in ops {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+
+                let correct: (Vec<T>, Vec<T>) = v.iter().partition(|x| op(x));
+                #[cfg(feature = "alloc")]
+                {
+                    let ans = v.iter().partition_simd(op);
+                    assert_eq!(
+                        ans,
+                        correct,
+                        "Failed for length {} and type {:?}",
+                        len,
+                        std::any::type_name::<T>()
+                    );
+                    let (mut matching, mut rest) = (vec![T::default()], vec![]);
+                    let counts = v.iter().partition_into_simd(&mut matching, &mut rest, op);
+                    assert_eq!(counts, (correct.0.len(), correct.1.len()));
+                    assert_eq!(matching[1..], correct.0);
+                    assert_eq!(rest, correct.1);
+                }
+
+                let mut ans = v.clone();
+                let split = ans.partition_in_place_simd(op);
+                assert_eq!(split, correct.0.len());
+                assert!(ans[..split].iter().all(op));
+                assert!(!ans[split..].iter().any(op));
+                let mut sorted = v.clone();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                ans.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                assert_eq!(ans, sorted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_partition() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+}
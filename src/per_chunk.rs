@@ -0,0 +1,342 @@
+use crate::argbest::argbest_kernel;
+use crate::count::count_kernel;
+use crate::dispatch::simd_kernel;
+use crate::mask::{mask_simd_internal, WORD_BITS};
+use crate::position::position_kernel;
+use alloc::collections::VecDeque;
+use alloc::{vec, vec::Vec};
+use core::slice;
+
+// Each of these dispatches once and then inlines the single-slice kernel
+// for every block, instead of dispatching again per block. `blocks` are the
+// `chunks` of the slice.
+
+fn position_per_block_internal<'a, I, F, T>(blocks: I, f: F) -> Vec<Option<usize>>
+where
+    I: Iterator<Item = &'a [T]>,
+    F: Fn(&T) -> bool,
+    T: 'a,
+{
+    simd_kernel! {
+        blocks.map(|block| position_kernel(block, &f)).collect()
+    }
+}
+
+fn count_per_block_internal<'a, I, F, T>(blocks: I, f: F) -> Vec<usize>
+where
+    I: Iterator<Item = &'a [T]>,
+    F: Fn(&T) -> bool,
+    T: 'a,
+{
+    simd_kernel! {
+        blocks.map(|block| count_kernel(block, &f)).collect()
+    }
+}
+
+fn argbest_per_block_internal<'a, I, T, F>(blocks: I, is_better: F) -> Vec<usize>
+where
+    I: Iterator<Item = &'a [T]>,
+    T: Copy + 'a,
+    F: Fn(&T, &T) -> bool,
+{
+    simd_kernel! {
+        blocks
+            // Blocks are never empty.
+            .map(|block| argbest_kernel(block, &is_better).unwrap())
+            .collect()
+    }
+}
+
+// Windows overlap, so answering each one separately would cost
+// `window_len` per window. These evaluate `f` once per element into a
+// bitmask instead and update the answer as the window slides.
+
+fn window_count(len: usize, window_len: usize) -> usize {
+    (len + 1).saturating_sub(window_len)
+}
+
+fn match_bits<T, F>(arr: &[T], f: F) -> impl Fn(usize) -> usize
+where
+    F: Fn(&T) -> bool,
+{
+    let mut mask = vec![0; arr.len().div_ceil(WORD_BITS)];
+    mask_simd_internal(arr, &mut mask, f);
+    move |i| ((mask[i / WORD_BITS] >> (i % WORD_BITS)) & 1) as usize
+}
+
+fn position_per_window_internal<T, F>(arr: &[T], window_len: usize, f: F) -> Vec<Option<usize>>
+where
+    F: Fn(&T) -> bool,
+{
+    let windows = window_count(arr.len(), window_len);
+    let bit = match_bits(arr, f);
+    let mut out = vec![None; windows];
+    // Swept from the back, so `next` is the first match at or after `i`.
+    let mut next = usize::MAX;
+    for i in (0..arr.len()).rev() {
+        if bit(i) == 1 {
+            next = i;
+        }
+        if i < windows && next < i + window_len {
+            out[i] = Some(next - i);
+        }
+    }
+    out
+}
+
+fn count_per_window_internal<T, F>(arr: &[T], window_len: usize, f: F) -> Vec<usize>
+where
+    F: Fn(&T) -> bool,
+{
+    let windows = window_count(arr.len(), window_len);
+    if windows == 0 {
+        return Vec::new();
+    }
+    let bit = match_bits(arr, f);
+    let mut out = Vec::with_capacity(windows);
+    let mut count: usize = (0..window_len).map(&bit).sum();
+    out.push(count);
+    for i in window_len..arr.len() {
+        count = count + bit(i) - bit(i - window_len);
+        out.push(count);
+    }
+    out
+}
+
+fn argbest_per_window_internal<T, F>(arr: &[T], window_len: usize, is_better: F) -> Vec<usize>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let mut out = Vec::with_capacity(window_count(arr.len(), window_len));
+    // Candidates of the current window in order. None of them is beaten by
+    // one after it, so the front is the first best.
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+    for (i, x) in arr.iter().enumerate() {
+        while candidates.back().is_some_and(|&j| is_better(x, &arr[j])) {
+            candidates.pop_back();
+        }
+        candidates.push_back(i);
+        if i + 1 >= window_len {
+            let start = i + 1 - window_len;
+            // The window moves by one, so at most the front falls out.
+            if candidates[0] < start {
+                candidates.pop_front();
+            }
+            out.push(candidates[0] - start);
+        }
+    }
+    out
+}
+
+/// Per-block queries: the slice is split like `chunks(chunk_len)`, or like
+/// `windows(window_len)` for the `_per_window` ones, and each block is
+/// answered separately. Indices are relative to their block.
+///
+/// The `_per_window` ones do not search every window again, they take
+/// linear time no matter how long the windows are.
+///
+/// All of them panic if `chunk_len` or `window_len` is 0.
+pub trait PerChunkSimd<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    /// Position of the first match in every block.
+    fn position_per_chunk_simd<F>(&self, chunk_len: usize, f: F) -> Vec<Option<usize>>
+    where
+        F: Fn(&T) -> bool;
+    /// Number of matches in every block.
+    fn count_per_chunk_simd<F>(&self, chunk_len: usize, f: F) -> Vec<usize>
+    where
+        F: Fn(&T) -> bool;
+    /// Index of the first maximum of every block.
+    fn argmax_per_chunk_simd(&self, chunk_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    /// Index of the first minimum of every block.
+    fn argmin_per_chunk_simd(&self, chunk_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    /// Position of the first match in every window.
+    fn position_per_window_simd<F>(&self, window_len: usize, f: F) -> Vec<Option<usize>>
+    where
+        F: Fn(&T) -> bool;
+    /// Number of matches in every window.
+    fn count_per_window_simd<F>(&self, window_len: usize, f: F) -> Vec<usize>
+    where
+        F: Fn(&T) -> bool;
+    /// Index of the first maximum of every window.
+    fn argmax_per_window_simd(&self, window_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    /// Index of the first minimum of every window.
+    fn argmin_per_window_simd(&self, window_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy;
+}
+
+impl<'a, T> PerChunkSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    fn position_per_chunk_simd<F>(&self, chunk_len: usize, f: F) -> Vec<Option<usize>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        assert!(chunk_len != 0, "chunk_len must not be 0");
+        position_per_block_internal(self.as_slice().chunks(chunk_len), f)
+    }
+    fn count_per_chunk_simd<F>(&self, chunk_len: usize, f: F) -> Vec<usize>
+    where
+        F: Fn(&T) -> bool,
+    {
+        assert!(chunk_len != 0, "chunk_len must not be 0");
+        count_per_block_internal(self.as_slice().chunks(chunk_len), f)
+    }
+    fn argmax_per_chunk_simd(&self, chunk_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        assert!(chunk_len != 0, "chunk_len must not be 0");
+        argbest_per_block_internal(self.as_slice().chunks(chunk_len), |x, max| x > max)
+    }
+    fn argmin_per_chunk_simd(&self, chunk_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        assert!(chunk_len != 0, "chunk_len must not be 0");
+        argbest_per_block_internal(self.as_slice().chunks(chunk_len), |x, min| x < min)
+    }
+    fn position_per_window_simd<F>(&self, window_len: usize, f: F) -> Vec<Option<usize>>
+    where
+        F: Fn(&T) -> bool,
+    {
+        assert!(window_len != 0, "window_len must not be 0");
+        position_per_window_internal(self.as_slice(), window_len, f)
+    }
+    fn count_per_window_simd<F>(&self, window_len: usize, f: F) -> Vec<usize>
+    where
+        F: Fn(&T) -> bool,
+    {
+        assert!(window_len != 0, "window_len must not be 0");
+        count_per_window_internal(self.as_slice(), window_len, f)
+    }
+    fn argmax_per_window_simd(&self, window_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        assert!(window_len != 0, "window_len must not be 0");
+        argbest_per_window_internal(self.as_slice(), window_len, |x, max| x > max)
+    }
+    fn argmin_per_window_simd(&self, window_len: usize) -> Vec<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        assert!(window_len != 0, "window_len must not be 0");
+        argbest_per_window_internal(self.as_slice(), window_len, |x, min| x < min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn argmax<T: Ord>(v: &[T]) -> usize {
+        let max = v.iter().max().unwrap();
+        v.iter().position(|x| x == max).unwrap()
+    }
+
+    fn argmin<T: Ord>(v: &[T]) -> usize {
+        let min = v.iter().min().unwrap();
+        v.iter().position(|x| x == min).unwrap()
+    }
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform + PartialEq + Copy + Default + Debug + Ord,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..1000 {
+            let chunk_len = rng.gen_range(1..100);
+            let mut v: Vec<T> = vec![T::default(); len];
+            for x in v.iter_mut() {
+                *x = rng.gen()
+            }
+            let op = |x: &T| *x < T::default();
+
+            let correct = v
+                .chunks(chunk_len)
+                .map(|c| c.iter().position(op))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                v.iter().position_per_chunk_simd(chunk_len, op),
+                correct,
+                "Failed for length {} and type {:?}",
+                len,
+                std::any::type_name::<T>()
+            );
+            let correct = v
+                .chunks(chunk_len)
+                .map(|c| c.iter().filter(|x| op(x)).count())
+                .collect::<Vec<_>>();
+            assert_eq!(v.iter().count_per_chunk_simd(chunk_len, op), correct);
+            let correct = v.chunks(chunk_len).map(argmax).collect::<Vec<_>>();
+            assert_eq!(v.iter().argmax_per_chunk_simd(chunk_len), correct);
+            let correct = v.chunks(chunk_len).map(argmin).collect::<Vec<_>>();
+            assert_eq!(v.iter().argmin_per_chunk_simd(chunk_len), correct);
+
+            let window_len = chunk_len;
+            let correct = v
+                .windows(window_len)
+                .map(|w| w.iter().position(op))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                v.iter().position_per_window_simd(window_len, op),
+                correct,
+                "Failed for length {}, window {} and type {:?}",
+                len,
+                window_len,
+                std::any::type_name::<T>()
+            );
+            let correct = v
+                .windows(window_len)
+                .map(|w| w.iter().filter(|x| op(x)).count())
+                .collect::<Vec<_>>();
+            assert_eq!(v.iter().count_per_window_simd(window_len, op), correct);
+            let correct = v.windows(window_len).map(argmax).collect::<Vec<_>>();
+            assert_eq!(v.iter().argmax_per_window_simd(window_len), correct);
+            let correct = v.windows(window_len).map(argmin).collect::<Vec<_>>();
+            assert_eq!(v.iter().argmin_per_window_simd(window_len), correct);
+        }
+    }
+
+    #[test]
+    fn test_simd_per_chunk() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_simd_per_chunk_zero_len() {
+        [1, 2, 3].iter().count_per_chunk_simd(0, |x| *x == 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_simd_per_window_zero_len() {
+        [1, 2, 3].iter().argmax_per_window_simd(0);
+    }
+}
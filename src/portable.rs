@@ -0,0 +1,346 @@
+use crate::dispatch::simd_kernel;
+use crate::LANE_COUNT;
+use core::simd::prelude::*;
+use core::simd::SimdElement;
+use core::slice;
+
+type Lanes<T> = Simd<T, LANE_COUNT>;
+type LaneMask<T> = Mask<<T as SimdElement>::Mask, LANE_COUNT>;
+
+/// Comparison of every element against a fixed value, e.g. `Lt(v)` matches
+/// the elements `x` with `x < v`.
+///
+/// Unlike a closure these map directly onto vector compares, which is what
+/// the explicit kernels of `PortableSimd` need. Arbitrary closures keep
+/// going through the autovectorized traits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cmp<T> {
+    Eq(T),
+    Ne(T),
+    Lt(T),
+    Le(T),
+    Gt(T),
+    Ge(T),
+}
+
+impl<T: PartialOrd> Cmp<T> {
+    /// Scalar version of the comparison.
+    pub fn test(&self, x: &T) -> bool {
+        match self {
+            Cmp::Eq(v) => x == v,
+            Cmp::Ne(v) => x != v,
+            Cmp::Lt(v) => x < v,
+            Cmp::Le(v) => x <= v,
+            Cmp::Gt(v) => x > v,
+            Cmp::Ge(v) => x >= v,
+        }
+    }
+}
+
+/// Primitive types with `core::simd` vectors.
+pub trait PortableElement: SimdElement + PartialOrd {
+    fn cmp_mask(cmp: Cmp<Self>, x: Lanes<Self>) -> LaneMask<Self>;
+}
+
+/// Primitive types with a total order.
+pub trait PortableOrd: PortableElement + Ord {
+    fn gt_mask(x: Lanes<Self>, y: Lanes<Self>) -> LaneMask<Self>;
+}
+
+macro_rules! impl_portable_element {
+    ($($t:ty),*) => {
+        $(
+            impl PortableElement for $t {
+                #[inline(always)]
+                fn cmp_mask(cmp: Cmp<Self>, x: Lanes<Self>) -> LaneMask<Self> {
+                    match cmp {
+                        Cmp::Eq(v) => x.simd_eq(Simd::splat(v)),
+                        Cmp::Ne(v) => x.simd_ne(Simd::splat(v)),
+                        Cmp::Lt(v) => x.simd_lt(Simd::splat(v)),
+                        Cmp::Le(v) => x.simd_le(Simd::splat(v)),
+                        Cmp::Gt(v) => x.simd_gt(Simd::splat(v)),
+                        Cmp::Ge(v) => x.simd_ge(Simd::splat(v)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_portable_ord {
+    ($($t:ty),*) => {
+        $(
+            impl PortableOrd for $t {
+                #[inline(always)]
+                fn gt_mask(x: Lanes<Self>, y: Lanes<Self>) -> LaneMask<Self> {
+                    x.simd_gt(y)
+                }
+            }
+        )*
+    };
+}
+
+impl_portable_element!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+impl_portable_ord!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+fn position_portable_internal<T: PortableElement>(v: &[T], cmp: Cmp<T>) -> Option<usize> {
+    simd_kernel! {
+        let (chunks, rem) = v.as_chunks::<LANE_COUNT>();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mask = T::cmp_mask(cmp, Simd::from_array(*chunk));
+            if mask.any() {
+                return Some(i * LANE_COUNT + mask.to_bitmask().trailing_zeros() as usize);
+            }
+        }
+        let offset = v.len() - rem.len();
+        rem.iter().position(|x| cmp.test(x)).map(|i| offset + i)
+    }
+}
+
+fn any_portable_internal<T: PortableElement>(v: &[T], cmp: Cmp<T>) -> bool {
+    simd_kernel! {
+        let (chunks, rem) = v.as_chunks::<LANE_COUNT>();
+        for chunk in chunks {
+            if T::cmp_mask(cmp, Simd::from_array(*chunk)).any() {
+                return true;
+            }
+        }
+        rem.iter().any(|x| cmp.test(x))
+    }
+}
+
+fn all_portable_internal<T: PortableElement>(v: &[T], cmp: Cmp<T>) -> bool {
+    simd_kernel! {
+        let (chunks, rem) = v.as_chunks::<LANE_COUNT>();
+        for chunk in chunks {
+            if !T::cmp_mask(cmp, Simd::from_array(*chunk)).all() {
+                return false;
+            }
+        }
+        rem.iter().all(|x| cmp.test(x))
+    }
+}
+
+fn count_portable_internal<T: PortableElement>(v: &[T], cmp: Cmp<T>) -> usize {
+    simd_kernel! {
+        let (chunks, rem) = v.as_chunks::<LANE_COUNT>();
+        let mut count = 0;
+        for chunk in chunks {
+            count += T::cmp_mask(cmp, Simd::from_array(*chunk))
+                .to_bitmask()
+                .count_ones() as usize;
+        }
+        count + rem.iter().filter(|x| cmp.test(x)).count()
+    }
+}
+
+/// Same lane layout as `argbest_autovec`: every lane keeps its best value
+/// and the chunk it came from, and ties are broken by index at the end.
+fn argmax_portable_internal<T: PortableOrd>(v: &[T]) -> Option<usize> {
+    simd_kernel! {
+        let (chunks, rem) = v.as_chunks::<LANE_COUNT>();
+        let mut best = match chunks.split_first() {
+            Some((first, rest)) => {
+                let mut vals = Simd::from_array(*first);
+                let mut chunk_idxs = Simd::<usize, LANE_COUNT>::splat(0);
+                for (i, chunk) in rest.iter().enumerate() {
+                    let x = Simd::from_array(*chunk);
+                    let better = T::gt_mask(x, vals);
+                    vals = better.select(x, vals);
+                    chunk_idxs = better
+                        .cast::<isize>()
+                        .select(Simd::splat(i + 1), chunk_idxs);
+                }
+                let (vals, chunk_idxs) = (vals.to_array(), chunk_idxs.to_array());
+                let mut best = (vals[0], chunk_idxs[0] * LANE_COUNT);
+                for lane in 1..LANE_COUNT {
+                    let idx = chunk_idxs[lane] * LANE_COUNT + lane;
+                    if vals[lane] > best.0 || (vals[lane] == best.0 && idx < best.1) {
+                        best = (vals[lane], idx);
+                    }
+                }
+                Some(best)
+            }
+            None => None,
+        };
+        let offset = v.len() - rem.len();
+        for (i, x) in rem.iter().enumerate() {
+            match best {
+                Some((val, _)) if *x <= val => {}
+                _ => best = Some((*x, offset + i)),
+            }
+        }
+        best.map(|(_, idx)| idx)
+    }
+}
+
+/// Kernels written with explicit `core::simd` vectors and masks instead of
+/// relying on LLVM to vectorize the loop. Nightly only, behind the
+/// `portable_simd` feature.
+pub trait PortableSimd<'a, T>
+where
+    T: PortableElement,
+{
+    fn position_portable_simd(&self, cmp: Cmp<T>) -> Option<usize>;
+    fn any_portable_simd(&self, cmp: Cmp<T>) -> bool;
+    fn all_portable_simd(&self, cmp: Cmp<T>) -> bool;
+    fn contains_portable_simd(&self, elem: &T) -> bool;
+    fn count_portable_simd(&self, cmp: Cmp<T>) -> usize;
+    fn argmax_portable_simd(&self) -> Option<usize>
+    where
+        T: PortableOrd;
+}
+
+impl<'a, T> PortableSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: PortableElement,
+{
+    /// Stops at the first chunk with a match and finds the element from the
+    /// trailing zeros of its bitmask.
+    fn position_portable_simd(&self, cmp: Cmp<T>) -> Option<usize> {
+        position_portable_internal(self.as_slice(), cmp)
+    }
+    fn any_portable_simd(&self, cmp: Cmp<T>) -> bool {
+        any_portable_internal(self.as_slice(), cmp)
+    }
+    fn all_portable_simd(&self, cmp: Cmp<T>) -> bool {
+        all_portable_internal(self.as_slice(), cmp)
+    }
+    fn contains_portable_simd(&self, elem: &T) -> bool {
+        any_portable_internal(self.as_slice(), Cmp::Eq(*elem))
+    }
+    fn count_portable_simd(&self, cmp: Cmp<T>) -> usize {
+        count_portable_internal(self.as_slice(), cmp)
+    }
+    /// Index of the first maximum, like `argmax_simd`.
+    fn argmax_portable_simd(&self) -> Option<usize>
+    where
+        T: PortableOrd,
+    {
+        argmax_portable_internal(self.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AllSimd, AnySimd, ArgmaxSimd, ContainsSimd, CountSimd, PositionSimd};
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn check_cmps<T>(v: &[T], needle: T)
+    where
+        T: PortableElement + Debug,
+    {
+        let cmps = [
+            Cmp::Eq(needle),
+            Cmp::Ne(needle),
+            Cmp::Lt(needle),
+            Cmp::Le(needle),
+            Cmp::Gt(needle),
+            Cmp::Ge(needle),
+        ];
+        for cmp in cmps {
+            let op = |x: &T| cmp.test(x);
+            assert_eq!(
+                v.iter().position_portable_simd(cmp),
+                v.iter().position_simd(op),
+                "Failed for {:?}, length {} and type {:?}",
+                cmp,
+                v.len(),
+                std::any::type_name::<T>()
+            );
+            assert_eq!(v.iter().any_portable_simd(cmp), v.iter().any_simd(op));
+            assert_eq!(v.iter().all_portable_simd(cmp), v.iter().all_simd(op));
+            assert_eq!(v.iter().count_portable_simd(cmp), v.iter().count_simd(op));
+        }
+        assert_eq!(
+            v.iter().contains_portable_simd(&needle),
+            v.iter().contains_simd(&needle)
+        );
+    }
+
+    fn test_simd_for_type<T>()
+    where
+        T: PortableElement + Default + Debug,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..500 {
+            let mut v: Vec<T> = vec![T::default(); len];
+            for x in v.iter_mut() {
+                *x = rng.gen()
+            }
+            let needle = match len {
+                0 => T::default(),
+                _ => v[rng.gen_range(0..len)],
+            };
+            check_cmps(&v, needle);
+            check_cmps(&v, T::default());
+        }
+    }
+
+    fn test_argmax_for_type<T>()
+    where
+        T: PortableOrd + rand::distributions::uniform::SampleUniform + Default + Debug,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..500 {
+            let mut v: Vec<T> = vec![T::default(); len];
+            for x in v.iter_mut() {
+                *x = rng.gen()
+            }
+            assert_eq!(v.iter().argmax_portable_simd(), v.iter().argmax_simd());
+            // Few distinct values, so the maximum repeats across lanes.
+            let (lo, hi) = (v.iter().min().copied(), v.iter().max().copied());
+            if let (Some(lo), Some(hi)) = (lo, hi) {
+                let picks = [lo, hi];
+                for x in v.iter_mut() {
+                    *x = picks[rng.gen_range(0..2)];
+                }
+            }
+            assert_eq!(v.iter().argmax_portable_simd(), v.iter().argmax_simd());
+        }
+    }
+
+    #[test]
+    fn test_simd_portable() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+
+    #[test]
+    fn test_simd_portable_nan() {
+        let mut v = vec![1.0f64; 100];
+        v[70] = f64::NAN;
+        check_cmps(&v, f64::NAN);
+        check_cmps(&v, 1.0);
+    }
+
+    #[test]
+    fn test_simd_portable_argmax() {
+        test_argmax_for_type::<i8>();
+        test_argmax_for_type::<i16>();
+        test_argmax_for_type::<i32>();
+        test_argmax_for_type::<i64>();
+        test_argmax_for_type::<u8>();
+        test_argmax_for_type::<u16>();
+        test_argmax_for_type::<u32>();
+        test_argmax_for_type::<u64>();
+        test_argmax_for_type::<usize>();
+        test_argmax_for_type::<isize>();
+    }
+}
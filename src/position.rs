@@ -1,10 +1,10 @@
+use crate::dispatch::simd_kernel;
 use crate::LANE_COUNT;
-use multiversion::multiversion;
-use std::slice;
+use core::slice;
 
 pub trait PositionSimd<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn position_simd<F>(&self, f: F) -> Option<usize>
     where
@@ -12,7 +12,7 @@ where
 }
 impl<'a, T> PositionSimd<'a, T> for slice::Iter<'a, T>
 where
-    T: std::cmp::PartialEq,
+    T: core::cmp::PartialEq,
 {
     fn position_simd<F>(&self, f: F) -> Option<usize>
     where
@@ -22,8 +22,18 @@ where
     }
 }
 
-#[multiversion(targets = "simd")]
 pub fn position_autovec<F, T>(arr: &[T], f: F) -> Option<usize>
+where
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        position_kernel(arr, &f)
+    }
+}
+
+/// Body of `position_autovec`, for callers that already dispatched.
+#[inline(always)]
+pub(crate) fn position_kernel<F, T>(arr: &[T], f: &F) -> Option<usize>
 where
     F: Fn(&T) -> bool,
 {
@@ -0,0 +1,196 @@
+use crate::dispatch::simd_kernel;
+use crate::LANE_COUNT;
+use core::slice;
+
+fn replace_where_internal<F, T>(v: &mut [T], f: F, value: T)
+where
+    T: Copy,
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        // Every element is written back so the branch becomes a blend.
+        for chunk in v.chunks_mut(LANE_COUNT) {
+            for x in chunk.iter_mut() {
+                *x = if f(x) { value } else { *x };
+            }
+        }
+    }
+}
+
+fn clamp_internal<T>(v: &mut [T], lo: T, hi: T)
+where
+    T: Copy + PartialOrd,
+{
+    simd_kernel! {
+        for chunk in v.chunks_mut(LANE_COUNT) {
+            for x in chunk.iter_mut() {
+                let clamped_lo = if *x < lo { lo } else { *x };
+                *x = if clamped_lo > hi { hi } else { clamped_lo };
+            }
+        }
+    }
+}
+
+pub trait ReplaceSimd<T>
+where
+    T: core::cmp::PartialEq + Copy,
+{
+    /// Replaces every element equal to `old` with `new`.
+    fn replace_simd(self, old: &T, new: T);
+    /// Replaces every element matching `f` with `value`.
+    fn replace_where_simd<F>(self, f: F, value: T)
+    where
+        F: Fn(&T) -> bool;
+    /// Replaces elements below `lo` with `lo` and above `hi` with `hi`.
+    /// NaNs are left alone.
+    ///
+    /// Panics if `lo > hi` or either bound is NaN.
+    fn clamp_simd(self, lo: T, hi: T)
+    where
+        T: core::cmp::PartialOrd;
+}
+
+impl<T> ReplaceSimd<T> for &mut [T]
+where
+    T: core::cmp::PartialEq + Copy,
+{
+    fn replace_simd(self, old: &T, new: T) {
+        replace_where_internal(self, |x| x == old, new)
+    }
+    fn replace_where_simd<F>(self, f: F, value: T)
+    where
+        F: Fn(&T) -> bool,
+    {
+        replace_where_internal(self, f, value)
+    }
+    fn clamp_simd(self, lo: T, hi: T)
+    where
+        T: core::cmp::PartialOrd,
+    {
+        assert!(lo <= hi, "clamp_simd needs lo <= hi");
+        clamp_internal(self, lo, hi)
+    }
+}
+
+impl<'a, T> ReplaceSimd<T> for slice::IterMut<'a, T>
+where
+    T: core::cmp::PartialEq + Copy,
+{
+    fn replace_simd(self, old: &T, new: T) {
+        self.into_slice().replace_simd(old, new)
+    }
+    fn replace_where_simd<F>(self, f: F, value: T)
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.into_slice().replace_where_simd(f, value)
+    }
+    fn clamp_simd(self, lo: T, hi: T)
+    where
+        T: core::cmp::PartialOrd,
+    {
+        self.into_slice().clamp_simd(lo, hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + Debug
+            + std::cmp::PartialEq
+            + std::cmp::PartialOrd,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..2000 {
+            let mut v: Vec<T> = vec![T::default(); len];
+            for x in v.iter_mut() {
+                // Plenty of duplicates so that `replace_simd` has work to do.
+                *x = if rng.gen_bool(0.3) {
+                    T::default()
+                } else {
+                    rng.gen()
+                };
+            }
+            let value: T = rng.gen();
+
+            let mut ans = v.clone();
+            ans.iter_mut().replace_simd(&T::default(), value);
+            let correct = v
+                .iter()
+                .map(|x| if *x == T::default() { value } else { *x })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                ans,
+                correct,
+                "Failed for length {} and type {:?}",
+                len,
+                std::any::type_name::<T>()
+            );
+
+            let mut ans = v.clone();
+            ans.as_mut_slice()
+                .replace_where_simd(|x| *x > value, T::default());
+            let correct = v
+                .iter()
+                .map(|x| if *x > value { T::default() } else { *x })
+                .collect::<Vec<_>>();
+            assert_eq!(ans, correct);
+
+            let (a, b) = (rng.gen(), rng.gen());
+            let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+            let mut ans = v.clone();
+            ans.iter_mut().clamp_simd(lo, hi);
+            let correct = v
+                .iter()
+                .map(|x| match x {
+                    x if *x < lo => lo,
+                    x if *x > hi => hi,
+                    x => *x,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(ans, correct);
+        }
+    }
+
+    #[test]
+    fn test_simd_replace() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+
+    #[test]
+    fn test_simd_clamp_nan() {
+        let mut v = [f32::NAN, -5.0, 0.5, 5.0];
+        v.iter_mut().clamp_simd(0.0, 1.0);
+        assert!(v[0].is_nan());
+        assert_eq!(v[1..], [0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_simd_clamp_bad_bounds() {
+        [1, 2, 3].iter_mut().clamp_simd(2, 1);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::dispatch::simd_kernel;
+use crate::filter::left_pack;
+use crate::mask::{mask_word, WORD_BITS};
+use alloc::vec::Vec;
+
+/// Moves the elements matching `f` to the front and returns how many there
+/// are. The write position never passes the read position, so every element
+/// is read before it can be overwritten.
+fn retain_internal<F, T>(v: &mut [T], f: F) -> usize
+where
+    T: Copy,
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        let mut written = 0;
+        let mut offsets = [0; WORD_BITS];
+        for chunk_start in (0..v.len()).step_by(WORD_BITS) {
+            let chunk_end = (chunk_start + WORD_BITS).min(v.len());
+            let mask = mask_word(&v[chunk_start..chunk_end], &f);
+            let len = left_pack(mask, &mut offsets);
+            if written == chunk_start && len == chunk_end - chunk_start {
+                // Nothing removed so far, the chunk is already in place.
+                written = chunk_end;
+                continue;
+            }
+            for o in &offsets[..len] {
+                v[written] = v[chunk_start + *o as usize];
+                written += 1;
+            }
+        }
+        written
+    }
+}
+
+pub trait RetainSimd<T> {
+    fn retain_simd<F>(&mut self, f: F)
+    where
+        F: Fn(&T) -> bool;
+}
+
+impl<T> RetainSimd<T> for Vec<T>
+where
+    T: core::cmp::PartialEq + Copy,
+{
+    /// Keeps only the elements matching `f`, in their original order. Same
+    /// result as `Vec::retain`, except that `f` may be called any number of
+    /// times per element.
+    fn retain_simd<F>(&mut self, f: F)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let len = retain_internal(self, f);
+        self.truncate(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + Debug
+            + std::cmp::PartialEq
+            + std::cmp::PartialOrd,
+        Standard: Distribution<T>,
+    {
+        for len in 0..5000 {
+            let ops = [
+                |x: &T| *x == T::default(),
+                |x: &T| *x != T::default(),
+                |x: &T| *x < T::default(),
+                |x: &T| *x > T::default(),
+                |x: &T| [T::default()].contains(x),
+            ];
+
+            for op in ops {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+
+                let mut ans = v.clone();
+                ans.retain_simd(op);
+                let mut correct = v.clone();
+                correct.retain(op);
+                assert_eq!(
+                    ans,
+                    correct,
+                    "Failed for length {} and type {:?}",
+                    len,
+                    std::any::type_name::<T>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_retain() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+}
@@ -0,0 +1,106 @@
+use crate::dispatch::simd_kernel;
+use crate::LANE_COUNT;
+use core::slice;
+
+pub trait RPositionSimd<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    fn rposition_simd<F>(&self, f: F) -> Option<usize>
+    where
+        F: Fn(&T) -> bool;
+}
+impl<'a, T> RPositionSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: core::cmp::PartialEq,
+{
+    fn rposition_simd<F>(&self, f: F) -> Option<usize>
+    where
+        F: Fn(&T) -> bool,
+    {
+        rposition_autovec(self.as_slice(), f)
+    }
+}
+
+pub fn rposition_autovec<F, T>(arr: &[T], f: F) -> Option<usize>
+where
+    F: Fn(&T) -> bool,
+{
+    simd_kernel! {
+        // rchunks_exact leaves the remainder at the front of the slice,
+        // so the remainder indices need no offset.
+        let mut chunks = arr.rchunks_exact(LANE_COUNT);
+        for (chunk_idx, chunk) in chunks.by_ref().enumerate() {
+            if chunk.iter().fold(false, |acc, x| acc | (f(x))) {
+                return Some(
+                    arr.len() - (chunk_idx + 1) * LANE_COUNT
+                        + unsafe { chunk.iter().rposition(f).unwrap_unchecked() },
+                );
+            }
+        }
+        chunks.remainder().iter().rposition(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform
+            + PartialEq
+            + Copy
+            + Default
+            + std::cmp::PartialEq
+            + std::cmp::PartialOrd,
+        Standard: Distribution<T>,
+    {
+        for len in 0..5000 {
+            let ops = [
+                |x: &T| *x == T::default(),
+                |x: &T| *x != T::default(),
+                |x: &T| *x < T::default(),
+                |x: &T| *x > T::default(),
+                |x: &T| [T::default()].contains(x),
+            ];
+
+            for op in ops {
+                let mut v: Vec<T> = vec![T::default(); len];
+                let mut rng = rand::thread_rng();
+                for x in v.iter_mut() {
+                    *x = rng.gen()
+                }
+
+                let ans = v.iter().rposition_simd(op);
+                let correct = v.iter().rposition(op);
+                assert_eq!(
+                    ans,
+                    correct,
+                    "Failed for length {} and type {:?}",
+                    len,
+                    std::any::type_name::<T>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    AllSimd, AnySimd, ArgmaxSimd, ArgminSimd, ContainsSimd, FilterSimd, FindSimd, PositionSimd,
+    SimdFilter,
+};
+
+/// The `slice::Iter` operations directly on `[T]`, which `Vec<T>`, `[T; N]`
+/// and `Box<[T]>` reach through auto-deref. Code generic over `AsRef<[T]>`
+/// has to call `.as_ref()` first:
+///
+/// ```
+/// use simd_itertools::SliceSimd;
+///
+/// fn has_zero(data: impl AsRef<[u8]>) -> bool {
+///     data.as_ref().contains_simd(&0)
+/// }
+///
+/// assert!(vec![1, 2, 3].any_simd(|x| *x == 2));
+/// assert_eq!([1, 5, 3].argmax_simd(), Some(1));
+/// assert!(has_zero(vec![0u8, 1].into_boxed_slice()));
+/// ```
+///
+/// This is implemented for `[T]` rather than for every `AsRef<[T]>`,
+/// because `slice::Iter` is `AsRef<[T]>` as well and the existing methods
+/// on it would become ambiguous.
+pub trait SliceSimd<T> {
+    fn any_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn all_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn position_simd<F>(&self, f: F) -> Option<usize>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn find_simd<'a, F>(&'a self, f: F) -> Option<&'a T>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool + 'a;
+    fn contains_simd(&self, elem: &T) -> bool
+    where
+        T: core::cmp::PartialEq;
+    fn filter_simd<'a, F>(&'a self, f: F) -> SimdFilter<'a, T, F>
+    where
+        T: core::cmp::PartialEq + Copy,
+        F: Fn(&T) -> bool + 'a;
+    fn argmax_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    fn argmin_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy;
+}
+
+impl<T> SliceSimd<T> for [T] {
+    fn any_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        self.iter().any_simd(f)
+    }
+    fn all_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        self.iter().all_simd(f)
+    }
+    fn position_simd<F>(&self, f: F) -> Option<usize>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        self.iter().position_simd(f)
+    }
+    fn find_simd<'a, F>(&'a self, f: F) -> Option<&'a T>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool + 'a,
+    {
+        self.iter().find_simd(f)
+    }
+    fn contains_simd(&self, elem: &T) -> bool
+    where
+        T: core::cmp::PartialEq,
+    {
+        self.iter().contains_simd(elem)
+    }
+    fn filter_simd<'a, F>(&'a self, f: F) -> SimdFilter<'a, T, F>
+    where
+        T: core::cmp::PartialEq + Copy,
+        F: Fn(&T) -> bool + 'a,
+    {
+        self.iter().filter_simd(f)
+    }
+    fn argmax_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        self.iter().argmax_simd()
+    }
+    fn argmin_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        self.iter().argmin_simd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    // Glob import on purpose: the slice::Iter methods must stay unambiguous.
+    #[allow(unused_imports)]
+    use crate::*;
+
+    fn check<S: AsRef<[i32]>>(data: S) {
+        let v = data.as_ref();
+        let op = |x: &i32| *x > 40;
+        assert_eq!(v.any_simd(op), v.iter().any_simd(op));
+        assert_eq!(v.all_simd(op), v.iter().all_simd(op));
+        assert_eq!(v.position_simd(op), v.iter().position_simd(op));
+        assert_eq!(v.find_simd(op), v.iter().find_simd(op));
+        assert_eq!(v.contains_simd(&42), v.iter().contains_simd(&42));
+        assert!(v.filter_simd(op).eq(v.iter().filter_simd(op)));
+        assert_eq!(v.argmax_simd(), v.iter().argmax_simd());
+        assert_eq!(v.argmin_simd(), v.iter().argmin_simd());
+    }
+
+    #[test]
+    fn test_simd_slice_containers() {
+        let mut rng = rand::thread_rng();
+        for len in 0..500 {
+            let v: Vec<i32> = (0..len).map(|_| rng.gen_range(0..50)).collect();
+            assert_eq!(v.any_simd(|x| *x == 7), v.contains(&7));
+            assert_eq!(v.as_slice().argmax_simd(), v.iter().argmax_simd());
+            let boxed: Box<[i32]> = v.clone().into_boxed_slice();
+            assert_eq!(
+                boxed.position_simd(|x| *x == 7),
+                v.iter().position(|x| *x == 7)
+            );
+            check(&v);
+            check(boxed);
+            check(v);
+        }
+        let arr = [3, 41, 7, 41];
+        assert_eq!(arr.argmax_simd(), Some(1));
+        assert_eq!(arr.find_simd(|x| *x > 40), Some(&41));
+        check(arr);
+    }
+}
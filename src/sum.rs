@@ -0,0 +1,539 @@
+use crate::dispatch::simd_kernel;
+use crate::float::{SimdFloat, SumMode};
+use crate::int::SimdInt;
+use crate::LANE_COUNT;
+use core::slice;
+
+pub(crate) fn wrapping_sum_internal<T, W>(v: &[T]) -> W
+where
+    T: SimdInt,
+    W: SimdInt + From<T>,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut acc = [W::ZERO; LANE_COUNT];
+        for chunk in chunks.by_ref() {
+            for (a, x) in acc.iter_mut().zip(chunk) {
+                *a = a.wrapping_add(W::from(*x));
+            }
+        }
+        acc.into_iter()
+            .chain(chunks.remainder().iter().map(|x| W::from(*x)))
+            .fold(W::ZERO, W::wrapping_add)
+    }
+}
+
+/// Wrapping sum together with the net number of times it wrapped around.
+/// The exact sum is `sum + wraps * 2^BITS`, so it fits in `T` iff `wraps == 0`.
+fn carrying_sum_internal<T>(v: &[T]) -> (T, i64)
+where
+    T: SimdInt,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut acc = [T::ZERO; LANE_COUNT];
+        let mut wraps = [0i64; LANE_COUNT];
+        for chunk in chunks.by_ref() {
+            for ((a, w), x) in acc.iter_mut().zip(wraps.iter_mut()).zip(chunk) {
+                let (sum, wrapped) = a.carrying_add(*x);
+                *a = sum;
+                *w += wrapped;
+            }
+        }
+        acc.into_iter()
+            .chain(chunks.remainder().iter().copied())
+            .fold((T::ZERO, wraps.iter().sum()), |(sum, wraps), x| {
+                let (sum, wrapped) = sum.carrying_add(x);
+                (sum, wraps + wrapped)
+            })
+    }
+}
+
+fn wrapping_product_internal<T, W>(v: &[T]) -> W
+where
+    T: SimdInt,
+    W: SimdInt + From<T>,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut acc = [W::ONE; LANE_COUNT];
+        for chunk in chunks.by_ref() {
+            for (a, x) in acc.iter_mut().zip(chunk) {
+                *a = a.wrapping_mul(W::from(*x));
+            }
+        }
+        acc.into_iter()
+            .chain(chunks.remainder().iter().map(|x| W::from(*x)))
+            .fold(W::ONE, W::wrapping_mul)
+    }
+}
+
+/// Wrapping product and whether any partial product overflowed.
+fn overflowing_product_internal<T>(v: &[T]) -> (T, bool)
+where
+    T: SimdInt,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut acc = [T::ONE; LANE_COUNT];
+        let mut overflows = [false; LANE_COUNT];
+        for chunk in chunks.by_ref() {
+            for ((a, o), x) in acc.iter_mut().zip(overflows.iter_mut()).zip(chunk) {
+                let (product, overflow) = a.overflowing_mul(*x);
+                *a = product;
+                *o |= overflow;
+            }
+        }
+        acc.into_iter()
+            .chain(chunks.remainder().iter().copied())
+            .fold(
+                (T::ONE, overflows.iter().any(|o| *o)),
+                |(product, overflowed), x| {
+                    let (product, overflow) = product.overflowing_mul(x);
+                    (product, overflowed | overflow)
+                },
+            )
+    }
+}
+
+/// Exact overflow check for products whose partial products overflowed.
+/// Needed because a partial product can overflow even when the full
+/// product fits, e.g. `-2 * -64 * -1 == i8::MIN`.
+fn checked_product_slow<T>(v: &[T], wrapped: T) -> Option<T>
+where
+    T: SimdInt,
+{
+    if v.contains(&T::ZERO) {
+        return Some(T::ZERO);
+    }
+    // Without zeros the magnitude never shrinks, so we can stop as soon as
+    // it is out of range for `T`.
+    let limit = T::MIN.unsigned_abs_u128().max(T::MAX.unsigned_abs_u128());
+    let mut magnitude: u128 = 1;
+    let mut negative = false;
+    for x in v {
+        negative ^= x.is_negative();
+        magnitude = magnitude.checked_mul(x.unsigned_abs_u128())?;
+        if magnitude > limit {
+            return None;
+        }
+    }
+    let fits = match negative {
+        true => magnitude <= T::MIN.unsigned_abs_u128(),
+        false => magnitude <= T::MAX.unsigned_abs_u128(),
+    };
+    // If the exact product fits then the wrapping product is exact.
+    fits.then_some(wrapped)
+}
+
+/// Element `i` is added to lane `i % LANE_COUNT`, remainder included.
+#[inline(always)]
+fn float_lane_sums<T>(v: &[T]) -> [T; LANE_COUNT]
+where
+    T: SimdFloat,
+{
+    let mut chunks = v.chunks_exact(LANE_COUNT);
+    let mut acc = [T::ZERO; LANE_COUNT];
+    for chunk in chunks.by_ref() {
+        for (a, x) in acc.iter_mut().zip(chunk) {
+            *a = *a + *x;
+        }
+    }
+    for (a, x) in acc.iter_mut().zip(chunks.remainder()) {
+        *a = *a + *x;
+    }
+    acc
+}
+
+#[inline(always)]
+fn reduce_lanes_pairwise<T>(mut acc: [T; LANE_COUNT]) -> T
+where
+    T: SimdFloat,
+{
+    let mut width = LANE_COUNT / 2;
+    while width > 0 {
+        for i in 0..width {
+            acc[i] = acc[i] + acc[i + width];
+        }
+        width /= 2;
+    }
+    acc[0]
+}
+
+/// Neumaier step: adds `x` to `sum` and the lost low bits to `comp`.
+#[inline(always)]
+fn neumaier_add<T>(sum: T, comp: T, x: T) -> (T, T)
+where
+    T: SimdFloat,
+{
+    let t = sum + x;
+    let lost = if sum.abs() >= x.abs() {
+        (sum - t) + x
+    } else {
+        (x - t) + sum
+    };
+    (t, comp + lost)
+}
+
+fn float_sum_lanes_internal<T>(v: &[T]) -> T
+where
+    T: SimdFloat,
+{
+    simd_kernel! {
+        reduce_lanes_pairwise(float_lane_sums(v))
+    }
+}
+
+fn float_sum_compensated_internal<T>(v: &[T]) -> T
+where
+    T: SimdFloat,
+{
+    simd_kernel! {
+        let mut chunks = v.chunks_exact(LANE_COUNT);
+        let mut sums = [T::ZERO; LANE_COUNT];
+        let mut comps = [T::ZERO; LANE_COUNT];
+        for chunk in chunks.by_ref() {
+            for ((s, c), x) in sums.iter_mut().zip(comps.iter_mut()).zip(chunk) {
+                (*s, *c) = neumaier_add(*s, *c, *x);
+            }
+        }
+        for ((s, c), x) in sums
+            .iter_mut()
+            .zip(comps.iter_mut())
+            .zip(chunks.remainder())
+        {
+            (*s, *c) = neumaier_add(*s, *c, *x);
+        }
+        let (sum, comp) = sums
+            .iter()
+            .fold((T::ZERO, reduce_lanes_pairwise(comps)), |(s, c), x| {
+                neumaier_add(s, c, *x)
+            });
+        // With infinities the compensation is NaN, the plain sum is right.
+        match sum.is_finite() {
+            true => sum + comp,
+            false => sum,
+        }
+    }
+}
+
+fn float_sum_pairwise_internal<T>(v: &[T]) -> T
+where
+    T: SimdFloat,
+{
+    simd_kernel! {
+        const BLOCK_LEN: usize = 8 * LANE_COUNT;
+        // Cascade summation: block sums are merged like a binary counter, so
+        // only sums covering the same number of blocks are added together.
+        let mut stack = [(T::ZERO, 0u32); usize::BITS as usize + 1];
+        let mut depth = 0;
+        for block in v.chunks(BLOCK_LEN) {
+            let mut sum = reduce_lanes_pairwise(float_lane_sums(block));
+            let mut level = 0;
+            while depth > 0 && stack[depth - 1].1 == level {
+                sum = stack[depth - 1].0 + sum;
+                level += 1;
+                depth -= 1;
+            }
+            stack[depth] = (sum, level);
+            depth += 1;
+        }
+        stack[..depth]
+            .iter()
+            .rev()
+            .fold(T::ZERO, |acc, (sum, _)| *sum + acc)
+    }
+}
+
+/// The checked and saturating variants look at the exact mathematical
+/// result, so unlike a sequential `checked_add` fold an intermediate
+/// overflow that cancels out later is not an error.
+pub trait SumSimd<'a, T>
+where
+    T: SimdInt,
+{
+    fn sum_simd(&self) -> T;
+    fn checked_sum_simd(&self) -> Option<T>;
+    fn saturating_sum_simd(&self) -> T;
+    fn widening_sum_simd<W>(&self) -> W
+    where
+        W: SimdInt + From<T>;
+    fn product_simd(&self) -> T;
+    fn checked_product_simd(&self) -> Option<T>;
+    fn saturating_product_simd(&self) -> T;
+    fn widening_product_simd<W>(&self) -> W
+    where
+        W: SimdInt + From<T>;
+}
+
+impl<'a, T> SumSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: SimdInt,
+{
+    /// Wrapping sum.
+    fn sum_simd(&self) -> T {
+        wrapping_sum_internal::<T, T>(self.as_slice())
+    }
+    /// `None` if the sum does not fit in `T`.
+    fn checked_sum_simd(&self) -> Option<T> {
+        match carrying_sum_internal(self.as_slice()) {
+            (sum, 0) => Some(sum),
+            _ => None,
+        }
+    }
+    fn saturating_sum_simd(&self) -> T {
+        match carrying_sum_internal(self.as_slice()) {
+            (sum, 0) => sum,
+            (_, wraps) if wraps > 0 => T::MAX,
+            _ => T::MIN,
+        }
+    }
+    /// Sums into a wider type, e.g. `u8` into `u64`. Wraps if even `W`
+    /// overflows.
+    fn widening_sum_simd<W>(&self) -> W
+    where
+        W: SimdInt + From<T>,
+    {
+        wrapping_sum_internal::<T, W>(self.as_slice())
+    }
+    /// Wrapping product.
+    fn product_simd(&self) -> T {
+        wrapping_product_internal::<T, T>(self.as_slice())
+    }
+    /// `None` if the product does not fit in `T`.
+    fn checked_product_simd(&self) -> Option<T> {
+        match overflowing_product_internal(self.as_slice()) {
+            (product, false) => Some(product),
+            (product, true) => checked_product_slow(self.as_slice(), product),
+        }
+    }
+    fn saturating_product_simd(&self) -> T {
+        match self.checked_product_simd() {
+            Some(product) => product,
+            None if self.as_slice().iter().filter(|x| x.is_negative()).count() % 2 == 1 => T::MIN,
+            None => T::MAX,
+        }
+    }
+    /// Multiplies into a wider type. Wraps if even `W` overflows.
+    fn widening_product_simd<W>(&self) -> W
+    where
+        W: SimdInt + From<T>,
+    {
+        wrapping_product_internal::<T, W>(self.as_slice())
+    }
+}
+
+pub trait FloatSumSimd<'a, T>
+where
+    T: SimdFloat,
+{
+    fn sum_float_simd(&self, mode: SumMode) -> T;
+}
+
+impl<'a, T> FloatSumSimd<'a, T> for slice::Iter<'a, T>
+where
+    T: SimdFloat,
+{
+    fn sum_float_simd(&self, mode: SumMode) -> T {
+        match mode {
+            SumMode::Lanes => float_sum_lanes_internal(self.as_slice()),
+            SumMode::Compensated => float_sum_compensated_internal(self.as_slice()),
+            SumMode::Pairwise => float_sum_pairwise_internal(self.as_slice()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dispatch::tests::for_each_target;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn gen_vec<T>(len: usize, small: bool) -> Vec<T>
+    where
+        T: SimdInt + TryFrom<i128>,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        (0..len)
+            .map(|_| match small {
+                true => T::try_from(rng.gen_range(-3..=3)).unwrap_or(T::ONE),
+                false => rng.gen(),
+            })
+            .collect()
+    }
+
+    fn test_simd_for_type<T>()
+    where
+        T: SimdInt + TryFrom<i128> + TryInto<i128> + Debug,
+        Standard: Distribution<T>,
+    {
+        let to_i128 = |x: T| -> i128 { x.try_into().ok().unwrap() };
+        let from_i128 = |x: i128| -> Option<T> { T::try_from(x).ok() };
+        for len in 0..500 {
+            for small in [false, true] {
+                let v: Vec<T> = gen_vec(len, small);
+
+                let exact_sum: i128 = v.iter().map(|x| to_i128(*x)).sum();
+                let wrapping_sum = v.iter().fold(T::ZERO, |a, b| a.wrapping_add(*b));
+                assert_eq!(v.iter().sum_simd(), wrapping_sum, "{:?}", v);
+                assert_eq!(v.iter().checked_sum_simd(), from_i128(exact_sum), "{:?}", v);
+                let saturating_sum = from_i128(exact_sum).unwrap_or(match exact_sum > 0 {
+                    true => T::MAX,
+                    false => T::MIN,
+                });
+                assert_eq!(v.iter().saturating_sum_simd(), saturating_sum, "{:?}", v);
+
+                let exact_product = match v.contains(&T::ZERO) {
+                    true => Some(0),
+                    false => v.iter().try_fold(1i128, |a, b| a.checked_mul(to_i128(*b))),
+                };
+                let wrapping_product = v.iter().fold(T::ONE, |a, b| a.wrapping_mul(*b));
+                assert_eq!(v.iter().product_simd(), wrapping_product, "{:?}", v);
+                assert_eq!(
+                    v.iter().checked_product_simd(),
+                    exact_product.and_then(from_i128),
+                    "Failed for length {} and type {:?} {:?}",
+                    len,
+                    std::any::type_name::<T>(),
+                    v
+                );
+                let saturating_product = exact_product.and_then(from_i128).unwrap_or(
+                    match v.iter().filter(|x| x.is_negative()).count() % 2 == 1 {
+                        true => T::MIN,
+                        false => T::MAX,
+                    },
+                );
+                assert_eq!(
+                    v.iter().saturating_product_simd(),
+                    saturating_product,
+                    "{:?}",
+                    v
+                );
+            }
+        }
+    }
+
+    fn test_widening_for_types<T, W>()
+    where
+        T: SimdInt + TryFrom<i128> + Debug,
+        W: SimdInt + From<T> + Debug,
+        Standard: Distribution<T>,
+    {
+        for len in 0..500 {
+            for small in [false, true] {
+                let v: Vec<T> = gen_vec(len, small);
+                let sum = v.iter().fold(W::ZERO, |a, b| a.wrapping_add(W::from(*b)));
+                let product = v.iter().fold(W::ONE, |a, b| a.wrapping_mul(W::from(*b)));
+                assert_eq!(v.iter().widening_sum_simd::<W>(), sum, "{:?}", v);
+                assert_eq!(v.iter().widening_product_simd::<W>(), product, "{:?}", v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_sum() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+
+    #[test]
+    fn test_simd_sum_widening() {
+        test_widening_for_types::<u8, u64>();
+        test_widening_for_types::<u8, u16>();
+        test_widening_for_types::<i8, i32>();
+        test_widening_for_types::<u16, u32>();
+        test_widening_for_types::<i32, i64>();
+        test_widening_for_types::<u32, u128>();
+    }
+
+    // Spells out the order `SumMode::Lanes` promises.
+    fn lanes_reference(v: &[f32]) -> f32 {
+        let mut lanes = [0.0f32; LANE_COUNT];
+        for (i, x) in v.iter().enumerate() {
+            lanes[i % LANE_COUNT] += x;
+        }
+        let mut width = LANE_COUNT / 2;
+        while width > 0 {
+            for i in 0..width {
+                lanes[i] += lanes[i + width];
+            }
+            width /= 2;
+        }
+        lanes[0]
+    }
+
+    #[test]
+    fn test_simd_sum_float() {
+        let mut rng = rand::thread_rng();
+        let modes = [SumMode::Lanes, SumMode::Compensated, SumMode::Pairwise];
+        for len in (0..5000).step_by(7) {
+            let v: Vec<f32> = (0..len).map(|_| rng.gen_range(-1000.0..1000.0)).collect();
+            let v64: Vec<f64> = v.iter().map(|x| *x as f64 * 1.1).collect();
+            let exact: f64 = v.iter().map(|x| *x as f64).sum();
+            let magnitude: f64 = v.iter().map(|x| x.abs() as f64).sum();
+
+            // Every target must give the same bits in every mode.
+            let mut expected = None;
+            for_each_target(|target| {
+                let bits = (
+                    modes.map(|mode| v.iter().sum_float_simd(mode).to_bits()),
+                    modes.map(|mode| v64.iter().sum_float_simd(mode).to_bits()),
+                );
+                assert_eq!(
+                    *expected.get_or_insert(bits),
+                    bits,
+                    "Failed for length {} on {}",
+                    len,
+                    target
+                );
+
+                let lanes = v.iter().sum_float_simd(SumMode::Lanes);
+                assert_eq!(lanes.to_bits(), lanes_reference(&v).to_bits(), "{:?}", v);
+                for mode in modes {
+                    let ans = v.iter().sum_float_simd(mode) as f64;
+                    assert!(
+                        (ans - exact).abs() <= magnitude * 1e-5 + 1e-3,
+                        "Failed for length {} and mode {:?}: {} vs {}",
+                        len,
+                        mode,
+                        ans,
+                        exact
+                    );
+                }
+                let ans = v.iter().sum_float_simd(SumMode::Compensated) as f64;
+                assert!((ans - exact).abs() <= exact.abs() * 1e-6 + 1e-3);
+            });
+        }
+    }
+
+    #[test]
+    fn test_simd_sum_float_compensated() {
+        let v = [1.0, 1e100, 1.0, -1e100];
+        assert_eq!(v.iter().sum_float_simd(SumMode::Compensated), 2.0);
+        let v = vec![0.1f64; 100_000];
+        let ans = v.iter().sum_float_simd(SumMode::Compensated);
+        assert!((ans - 10_000.0).abs() < 1e-9, "{}", ans);
+        let v = [1.0, f64::INFINITY, 2.0];
+        assert_eq!(v.iter().sum_float_simd(SumMode::Compensated), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_simd_checked_product_min() {
+        // Every grouping but the one below overflows on a partial product.
+        let v: [i8; 3] = [-2, -64, -1];
+        assert_eq!(v.iter().checked_product_simd(), Some(i8::MIN));
+        assert_eq!(v.iter().saturating_product_simd(), i8::MIN);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{AllSimd, AnySimd, ArgmaxSimd, ArgminSimd, ContainsSimd, CountSimd, PositionSimd};
+use alloc::collections::VecDeque;
+
+/// The search and reduction operations on a `VecDeque`. They run the slice
+/// kernels over both halves of `as_slices()` and fix up the indices of the
+/// second half.
+///
+/// These live on the deque itself because `vec_deque::Iter::as_slices` is
+/// not stable yet.
+pub trait VecDequeSimd<T> {
+    fn any_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn all_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn position_simd<F>(&self, f: F) -> Option<usize>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn find_simd<F>(&self, f: F) -> Option<&T>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn contains_simd(&self, elem: &T) -> bool
+    where
+        T: core::cmp::PartialEq;
+    fn count_simd<F>(&self, f: F) -> usize
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool;
+    fn argmax_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy;
+    fn argmin_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy;
+}
+
+impl<T> VecDequeSimd<T> for VecDeque<T> {
+    fn any_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        let (a, b) = self.as_slices();
+        a.iter().any_simd(&f) || b.iter().any_simd(&f)
+    }
+    fn all_simd<F>(&self, f: F) -> bool
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        let (a, b) = self.as_slices();
+        a.iter().all_simd(&f) && b.iter().all_simd(&f)
+    }
+    fn position_simd<F>(&self, f: F) -> Option<usize>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        let (a, b) = self.as_slices();
+        match a.iter().position_simd(&f) {
+            Some(idx) => Some(idx),
+            None => b.iter().position_simd(&f).map(|idx| a.len() + idx),
+        }
+    }
+    fn find_simd<F>(&self, f: F) -> Option<&T>
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        self.position_simd(f).map(|idx| &self[idx])
+    }
+    fn contains_simd(&self, elem: &T) -> bool
+    where
+        T: core::cmp::PartialEq,
+    {
+        let (a, b) = self.as_slices();
+        a.iter().contains_simd(elem) || b.iter().contains_simd(elem)
+    }
+    fn count_simd<F>(&self, f: F) -> usize
+    where
+        T: core::cmp::PartialEq,
+        F: Fn(&T) -> bool,
+    {
+        let (a, b) = self.as_slices();
+        a.iter().count_simd(&f) + b.iter().count_simd(&f)
+    }
+    fn argmax_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        let (a, b) = self.as_slices();
+        // On a tie the first half wins, since it comes first.
+        match (a.iter().argmax_simd(), b.iter().argmax_simd()) {
+            (Some(i), Some(j)) if b[j] > a[i] => Some(a.len() + j),
+            (Some(i), _) => Some(i),
+            (None, j) => j.map(|j| a.len() + j),
+        }
+    }
+    fn argmin_simd(&self) -> Option<usize>
+    where
+        T: core::cmp::Ord + Copy,
+    {
+        let (a, b) = self.as_slices();
+        match (a.iter().argmin_simd(), b.iter().argmin_simd()) {
+            (Some(i), Some(j)) if b[j] < a[i] => Some(a.len() + j),
+            (Some(i), _) => Some(i),
+            (None, j) => j.map(|j| a.len() + j),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    fn test_simd_for_type<T>()
+    where
+        T: rand::distributions::uniform::SampleUniform + PartialEq + Copy + Default + Debug + Ord,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..300 {
+            for _ in 0..5 {
+                // Pushing to the front makes the deque wrap around, so both
+                // halves of `as_slices` are usually non-empty.
+                let mut v: VecDeque<T> = VecDeque::with_capacity(len);
+                for _ in 0..len {
+                    match rng.gen_bool(0.5) {
+                        true => v.push_front(rng.gen()),
+                        false => v.push_back(rng.gen()),
+                    }
+                }
+                let op = |x: &T| *x > T::default();
+                let needle = v.get(len / 2).copied().unwrap_or_default();
+                let max = v.iter().max();
+                let min = v.iter().min();
+
+                assert_eq!(v.any_simd(op), v.iter().any(op), "{:?}", v);
+                assert_eq!(v.all_simd(op), v.iter().all(op), "{:?}", v);
+                assert_eq!(v.position_simd(op), v.iter().position(op));
+                assert_eq!(v.find_simd(op), v.iter().find(|x| op(x)));
+                assert_eq!(v.contains_simd(&needle), v.contains(&needle));
+                assert_eq!(v.count_simd(op), v.iter().filter(|x| op(x)).count());
+                assert_eq!(
+                    v.argmax_simd(),
+                    v.iter().position(|x| Some(x) == max),
+                    "Failed for length {} and type {:?} {:?}",
+                    len,
+                    std::any::type_name::<T>(),
+                    v
+                );
+                assert_eq!(v.argmin_simd(), v.iter().position(|x| Some(x) == min));
+            }
+        }
+    }
+
+    #[test]
+    fn test_simd_vec_deque() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+    }
+}
@@ -0,0 +1,345 @@
+use crate::dispatch::{selected_target, Target};
+use core::arch::x86_64::*;
+use core::mem::size_of;
+
+/// Instruction sets with a kernel, from the oldest to the newest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Level {
+    Sse2,
+    Avx2,
+    #[cfg(feature = "avx512")]
+    Avx512bw,
+}
+
+impl Level {
+    /// Kernel to use for `target`, `None` if it has none.
+    pub(crate) fn from_target(target: Target) -> Option<Level> {
+        match target {
+            Target::Sse2 | Target::Sse42 => Some(Level::Sse2),
+            Target::Avx2 => Some(Level::Avx2),
+            #[cfg(feature = "avx512")]
+            Target::Avx512bw => Some(Level::Avx512bw),
+            // Never supported without the `avx512` feature.
+            #[cfg(not(feature = "avx512"))]
+            Target::Avx512bw => Some(Level::Avx2),
+            Target::Generic | Target::Neon => None,
+        }
+    }
+    /// Kernel for `dispatch::selected_target()`.
+    pub(crate) fn selected() -> Option<Level> {
+        Level::from_target(selected_target())
+    }
+    fn is_available(self) -> bool {
+        match self {
+            Level::Sse2 => Target::Sse2.is_supported(),
+            Level::Avx2 => Target::Avx2.is_supported(),
+            #[cfg(feature = "avx512")]
+            Level::Avx512bw => Target::Avx512bw.is_supported(),
+        }
+    }
+}
+
+/// Element types with an equality compare for every level.
+///
+/// The `eq_mask_*` functions compare one vector of elements at `p` with
+/// `needle`. Every element owns `1 << SHIFT` consecutive bits of the SSE2 and
+/// AVX2 masks and one bit of the AVX-512 mask, all set if it is equal.
+pub(crate) trait X86Element: Copy + PartialEq {
+    const SHIFT: u32;
+    unsafe fn eq_mask_sse2(p: *const Self, needle: Self) -> u64;
+    unsafe fn eq_mask_avx2(p: *const Self, needle: Self) -> u64;
+    #[cfg(feature = "avx512")]
+    unsafe fn eq_mask_avx512(p: *const Self, needle: Self) -> u64;
+}
+
+/// SSE2 has no 64-bit compare, so both 32-bit halves have to match.
+#[inline(always)]
+unsafe fn _mm_cmpeq_epi64_sse2(a: __m128i, b: __m128i) -> __m128i {
+    let eq = _mm_cmpeq_epi32(a, b);
+    _mm_and_si128(eq, _mm_shuffle_epi32::<0b10_11_00_01>(eq))
+}
+
+macro_rules! impl_x86_int {
+    ($($t:ty => $int:ty, $shift:expr,
+        $set1:ident, $cmpeq:ident,
+        $set1_256:ident, $cmpeq_256:ident,
+        $set1_512:ident, $cmpeq_512:ident;)*) => {
+        $(
+            impl X86Element for $t {
+                const SHIFT: u32 = $shift;
+                #[inline(always)]
+                unsafe fn eq_mask_sse2(p: *const Self, needle: Self) -> u64 {
+                    let eq = $cmpeq(_mm_loadu_si128(p as *const __m128i), $set1(needle as $int));
+                    _mm_movemask_epi8(eq) as u32 as u64
+                }
+                #[inline(always)]
+                unsafe fn eq_mask_avx2(p: *const Self, needle: Self) -> u64 {
+                    let eq = $cmpeq_256(
+                        _mm256_loadu_si256(p as *const __m256i),
+                        $set1_256(needle as $int),
+                    );
+                    _mm256_movemask_epi8(eq) as u32 as u64
+                }
+                #[cfg(feature = "avx512")]
+                #[inline(always)]
+                unsafe fn eq_mask_avx512(p: *const Self, needle: Self) -> u64 {
+                    $cmpeq_512(
+                        _mm512_loadu_si512(p as *const __m512i),
+                        $set1_512(needle as $int),
+                    ) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_x86_int! {
+    u8 => i8, 0, _mm_set1_epi8, _mm_cmpeq_epi8,
+        _mm256_set1_epi8, _mm256_cmpeq_epi8, _mm512_set1_epi8, _mm512_cmpeq_epi8_mask;
+    i8 => i8, 0, _mm_set1_epi8, _mm_cmpeq_epi8,
+        _mm256_set1_epi8, _mm256_cmpeq_epi8, _mm512_set1_epi8, _mm512_cmpeq_epi8_mask;
+    u16 => i16, 1, _mm_set1_epi16, _mm_cmpeq_epi16,
+        _mm256_set1_epi16, _mm256_cmpeq_epi16, _mm512_set1_epi16, _mm512_cmpeq_epi16_mask;
+    i16 => i16, 1, _mm_set1_epi16, _mm_cmpeq_epi16,
+        _mm256_set1_epi16, _mm256_cmpeq_epi16, _mm512_set1_epi16, _mm512_cmpeq_epi16_mask;
+    u32 => i32, 2, _mm_set1_epi32, _mm_cmpeq_epi32,
+        _mm256_set1_epi32, _mm256_cmpeq_epi32, _mm512_set1_epi32, _mm512_cmpeq_epi32_mask;
+    i32 => i32, 2, _mm_set1_epi32, _mm_cmpeq_epi32,
+        _mm256_set1_epi32, _mm256_cmpeq_epi32, _mm512_set1_epi32, _mm512_cmpeq_epi32_mask;
+    u64 => i64, 3, _mm_set1_epi64x, _mm_cmpeq_epi64_sse2,
+        _mm256_set1_epi64x, _mm256_cmpeq_epi64, _mm512_set1_epi64, _mm512_cmpeq_epi64_mask;
+    i64 => i64, 3, _mm_set1_epi64x, _mm_cmpeq_epi64_sse2,
+        _mm256_set1_epi64x, _mm256_cmpeq_epi64, _mm512_set1_epi64, _mm512_cmpeq_epi64_mask;
+    usize => i64, 3, _mm_set1_epi64x, _mm_cmpeq_epi64_sse2,
+        _mm256_set1_epi64x, _mm256_cmpeq_epi64, _mm512_set1_epi64, _mm512_cmpeq_epi64_mask;
+    isize => i64, 3, _mm_set1_epi64x, _mm_cmpeq_epi64_sse2,
+        _mm256_set1_epi64x, _mm256_cmpeq_epi64, _mm512_set1_epi64, _mm512_cmpeq_epi64_mask;
+}
+
+// Floats compare with `_CMP_EQ_OQ` like `==`: NaN never matches and
+// `0.0 == -0.0`, so the ints' bitwise compare cannot be reused.
+impl X86Element for f32 {
+    const SHIFT: u32 = 0;
+    #[inline(always)]
+    unsafe fn eq_mask_sse2(p: *const Self, needle: Self) -> u64 {
+        _mm_movemask_ps(_mm_cmpeq_ps(_mm_loadu_ps(p), _mm_set1_ps(needle))) as u32 as u64
+    }
+    #[inline(always)]
+    unsafe fn eq_mask_avx2(p: *const Self, needle: Self) -> u64 {
+        let eq = _mm256_cmp_ps::<_CMP_EQ_OQ>(_mm256_loadu_ps(p), _mm256_set1_ps(needle));
+        _mm256_movemask_ps(eq) as u32 as u64
+    }
+    #[cfg(feature = "avx512")]
+    #[inline(always)]
+    unsafe fn eq_mask_avx512(p: *const Self, needle: Self) -> u64 {
+        _mm512_cmp_ps_mask::<_CMP_EQ_OQ>(_mm512_loadu_ps(p), _mm512_set1_ps(needle)) as u64
+    }
+}
+
+impl X86Element for f64 {
+    const SHIFT: u32 = 0;
+    #[inline(always)]
+    unsafe fn eq_mask_sse2(p: *const Self, needle: Self) -> u64 {
+        _mm_movemask_pd(_mm_cmpeq_pd(_mm_loadu_pd(p), _mm_set1_pd(needle))) as u32 as u64
+    }
+    #[inline(always)]
+    unsafe fn eq_mask_avx2(p: *const Self, needle: Self) -> u64 {
+        let eq = _mm256_cmp_pd::<_CMP_EQ_OQ>(_mm256_loadu_pd(p), _mm256_set1_pd(needle));
+        _mm256_movemask_pd(eq) as u32 as u64
+    }
+    #[cfg(feature = "avx512")]
+    #[inline(always)]
+    unsafe fn eq_mask_avx512(p: *const Self, needle: Self) -> u64 {
+        _mm512_cmp_pd_mask::<_CMP_EQ_OQ>(_mm512_loadu_pd(p), _mm512_set1_pd(needle)) as u64
+    }
+}
+
+macro_rules! level_kernels {
+    ($feature:literal, $bytes:expr, $eq_mask:ident, $shift:ident, $position:ident, $count:ident) => {
+        #[target_feature(enable = $feature)]
+        unsafe fn $position<T: X86Element>(v: &[T], needle: T) -> Option<usize> {
+            let lanes = $bytes / size_of::<T>();
+            let mut chunks = v.chunks_exact(lanes);
+            for (i, chunk) in chunks.by_ref().enumerate() {
+                let mask = T::$eq_mask(chunk.as_ptr(), needle);
+                if mask != 0 {
+                    return Some(i * lanes + (mask.trailing_zeros() >> $shift::<T>()) as usize);
+                }
+            }
+            let offset = v.len() - chunks.remainder().len();
+            chunks
+                .remainder()
+                .iter()
+                .position(|x| *x == needle)
+                .map(|i| offset + i)
+        }
+
+        #[target_feature(enable = $feature)]
+        unsafe fn $count<T: X86Element>(v: &[T], needle: T) -> usize {
+            let lanes = $bytes / size_of::<T>();
+            let mut chunks = v.chunks_exact(lanes);
+            let mut bits = 0;
+            for chunk in chunks.by_ref() {
+                bits += T::$eq_mask(chunk.as_ptr(), needle).count_ones() as usize;
+            }
+            (bits >> $shift::<T>()) + chunks.remainder().iter().filter(|x| **x == needle).count()
+        }
+    };
+}
+
+#[inline(always)]
+fn mask_shift<T: X86Element>() -> u32 {
+    T::SHIFT
+}
+
+#[cfg(feature = "avx512")]
+#[inline(always)]
+fn no_shift<T: X86Element>() -> u32 {
+    0
+}
+
+level_kernels!(
+    "sse2",
+    16,
+    eq_mask_sse2,
+    mask_shift,
+    position_sse2,
+    count_sse2
+);
+level_kernels!(
+    "avx2",
+    32,
+    eq_mask_avx2,
+    mask_shift,
+    position_avx2,
+    count_avx2
+);
+#[cfg(feature = "avx512")]
+level_kernels!(
+    "avx512f,avx512bw",
+    64,
+    eq_mask_avx512,
+    no_shift,
+    position_avx512bw,
+    count_avx512bw
+);
+
+/// Index of the first element equal to `needle` using the kernel for `level`.
+///
+/// Panics if `level` is not available on this CPU.
+pub(crate) fn position_with<T: X86Element>(level: Level, v: &[T], needle: T) -> Option<usize> {
+    assert!(level.is_available(), "{:?} is not supported", level);
+    // SAFETY: the required target features were checked above.
+    unsafe {
+        match level {
+            Level::Sse2 => position_sse2(v, needle),
+            Level::Avx2 => position_avx2(v, needle),
+            #[cfg(feature = "avx512")]
+            Level::Avx512bw => position_avx512bw(v, needle),
+        }
+    }
+}
+
+/// Number of elements equal to `needle` using the kernel for `level`.
+///
+/// Panics if `level` is not available on this CPU.
+pub(crate) fn count_with<T: X86Element>(level: Level, v: &[T], needle: T) -> usize {
+    assert!(level.is_available(), "{:?} is not supported", level);
+    // SAFETY: the required target features were checked above.
+    unsafe {
+        match level {
+            Level::Sse2 => count_sse2(v, needle),
+            Level::Avx2 => count_avx2(v, needle),
+            #[cfg(feature = "avx512")]
+            Level::Avx512bw => count_avx512bw(v, needle),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Standard;
+    use rand::prelude::Distribution;
+    use rand::Rng;
+    use std::fmt::Debug;
+
+    const LEVELS: &[Level] = &[
+        Level::Sse2,
+        Level::Avx2,
+        #[cfg(feature = "avx512")]
+        Level::Avx512bw,
+    ];
+
+    fn check<T: X86Element + Debug>(v: &[T], needle: T) {
+        for &level in LEVELS.iter().filter(|level| level.is_available()) {
+            assert_eq!(
+                position_with(level, v, needle),
+                v.iter().position(|x| *x == needle),
+                "Failed for {:?}, length {} and type {:?}",
+                level,
+                v.len(),
+                std::any::type_name::<T>()
+            );
+            assert_eq!(
+                count_with(level, v, needle),
+                v.iter().filter(|x| **x == needle).count(),
+                "Failed for {:?}, length {} and type {:?}",
+                level,
+                v.len(),
+                std::any::type_name::<T>()
+            );
+        }
+    }
+
+    fn test_simd_for_type<T>()
+    where
+        T: X86Element + rand::distributions::uniform::SampleUniform + Default + Debug,
+        Standard: Distribution<T>,
+    {
+        let mut rng = rand::thread_rng();
+        for len in 0..300 {
+            // Only a few distinct values, so there are repeated matches.
+            let mut v: Vec<T> = vec![T::default(); len];
+            let values: [T; 3] = [rng.gen(), rng.gen(), rng.gen()];
+            for x in v.iter_mut() {
+                *x = values[rng.gen_range(0..3)];
+            }
+            for needle in values {
+                check(&v, needle);
+            }
+            check(&v, rng.gen());
+        }
+    }
+
+    #[test]
+    fn test_simd_x86_levels() {
+        test_simd_for_type::<i8>();
+        test_simd_for_type::<i16>();
+        test_simd_for_type::<i32>();
+        test_simd_for_type::<i64>();
+        test_simd_for_type::<u8>();
+        test_simd_for_type::<u16>();
+        test_simd_for_type::<u32>();
+        test_simd_for_type::<u64>();
+        test_simd_for_type::<usize>();
+        test_simd_for_type::<isize>();
+        test_simd_for_type::<f32>();
+        test_simd_for_type::<f64>();
+    }
+
+    #[test]
+    fn test_simd_x86_float_eq() {
+        // Same bits in one half of the element must not be enough.
+        let v = [1u64 << 32, 1, 0, (1 << 32) | 1];
+        check(&v, 1);
+        check(&v, 1 << 32);
+        let v = [0.0, f64::NAN, -0.0, 1.0, f64::NAN, 2.0, -0.0, 0.0, 5.0];
+        check(&v, -0.0);
+        check(&v, 0.0);
+        check(&v, f64::NAN);
+        let v: Vec<f32> = v.iter().map(|x| *x as f32).collect();
+        check(&v, -0.0);
+        check(&v, f32::NAN);
+    }
+}